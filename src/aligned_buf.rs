@@ -0,0 +1,85 @@
+use std::ops::{Deref, DerefMut};
+
+/// Alignment of an [`AlignedBuf`] in bytes, the page size on most
+/// platforms.
+const BUF_ALIGN: usize = 4096;
+
+/// Zero initialized receive buffer aligned to [`BUF_ALIGN`].
+///
+/// Some USB stacks can only transfer directly to or from page aligned
+/// memory.
+/// The capture worker of [`RingCapture`] reuses a single `AlignedBuf` for
+/// every transfer, any gain from the alignment is platform dependent.
+///
+/// [`RingCapture`]: crate::RingCapture
+pub(crate) struct AlignedBuf {
+    /// Allocation with room to align the start.
+    buf: Vec<u8>,
+    /// Offset of the aligned start in `buf`.
+    offset: usize,
+    len: usize,
+}
+
+impl AlignedBuf {
+    /// Allocate a zeroed buffer of `len` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` overflows the maximum allocation size, and aborts if
+    /// the allocation fails.
+    pub(crate) fn new(len: usize) -> AlignedBuf {
+        let size: usize = len
+            .checked_add(BUF_ALIGN - 1)
+            .expect("AlignedBuf size overflow");
+        let buf: Vec<u8> = vec![0; size];
+        let offset: usize = buf.as_ptr().align_offset(BUF_ALIGN);
+        assert!(offset < BUF_ALIGN, "failed to align AlignedBuf");
+        AlignedBuf { buf, offset, len }
+    }
+}
+
+impl Deref for AlignedBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.buf[self.offset..self.offset + self.len]
+    }
+}
+
+impl DerefMut for AlignedBuf {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.buf[self.offset..self.offset + self.len]
+    }
+}
+
+#[cfg(test)]
+mod new {
+    use super::{AlignedBuf, BUF_ALIGN};
+
+    #[test]
+    fn aligned() {
+        for len in [1, 511, BUF_ALIGN, 128 * 1024] {
+            let buf: AlignedBuf = AlignedBuf::new(len);
+            assert_eq!(buf.as_ptr() as usize % BUF_ALIGN, 0);
+            assert_eq!(buf.len(), len);
+        }
+    }
+
+    #[test]
+    fn zeroed() {
+        assert!(AlignedBuf::new(8192).iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn write() {
+        let mut buf: AlignedBuf = AlignedBuf::new(4);
+        buf.copy_from_slice(&[1, 2, 3, 4]);
+        assert_eq!(&buf[..], [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn empty() {
+        let buf: AlignedBuf = AlignedBuf::new(0);
+        assert!(buf.is_empty());
+    }
+}
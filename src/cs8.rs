@@ -0,0 +1,180 @@
+use crate::Error;
+use std::{
+    fs::File,
+    io::{BufWriter, ErrorKind, Read, Write},
+    path::Path,
+};
+
+/// Size of the capture file header in bytes.
+pub const CS8_HEADER_SIZE: usize = 32;
+
+/// Capture file magic.
+const MAGIC: [u8; 4] = *b"HRF\0";
+/// Current capture file format version.
+const VERSION: u16 = 1;
+/// Sample format identifier for interleaved signed 8-bit IQ.
+const FORMAT_CS8: u16 = 1;
+
+/// Metadata stored in the header of a capture file.
+///
+/// # File format
+///
+/// A capture file is a 32 byte header followed by the samples as
+/// interleaved signed 8-bit IQ, exactly as received from the radio.
+/// All header fields are little endian.
+///
+/// | Offset | Size | Field                                   |
+/// |--------|------|-----------------------------------------|
+/// | 0      | 4    | Magic, `b"HRF\0"`                       |
+/// | 4      | 2    | Format version, currently 1             |
+/// | 6      | 2    | Sample format, 1 for interleaved i8     |
+/// | 8      | 4    | Sample rate in Hz                       |
+/// | 12     | 4    | Reserved, zero                          |
+/// | 16     | 8    | Center frequency in Hz                  |
+/// | 24     | 8    | Reserved, zero                          |
+///
+/// Readers reject files with a newer version than they support, reserved
+/// fields may be assigned in later versions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CaptureInfo {
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+    /// Center frequency in Hz.
+    pub center_freq: u64,
+}
+
+impl CaptureInfo {
+    fn to_bytes(self) -> [u8; CS8_HEADER_SIZE] {
+        let mut buf: [u8; CS8_HEADER_SIZE] = [0; CS8_HEADER_SIZE];
+        buf[0..4].copy_from_slice(&MAGIC);
+        buf[4..6].copy_from_slice(&VERSION.to_le_bytes());
+        buf[6..8].copy_from_slice(&FORMAT_CS8.to_le_bytes());
+        buf[8..12].copy_from_slice(&self.sample_rate.to_le_bytes());
+        buf[16..24].copy_from_slice(&self.center_freq.to_le_bytes());
+        buf
+    }
+
+    fn from_bytes(buf: &[u8; CS8_HEADER_SIZE]) -> Result<CaptureInfo, Error> {
+        let version: u16 = u16::from_le_bytes([buf[4], buf[5]]);
+        let format: u16 = u16::from_le_bytes([buf[6], buf[7]]);
+        if buf[0..4] != MAGIC || version == 0 || version > VERSION || format != FORMAT_CS8 {
+            return Err(Error::Io(ErrorKind::InvalidData));
+        }
+        Ok(CaptureInfo {
+            sample_rate: u32::from_le_bytes(buf[8..12].try_into().unwrap()),
+            center_freq: u64::from_le_bytes(buf[16..24].try_into().unwrap()),
+        })
+    }
+}
+
+/// Write samples to a capture file with a header recording the sample rate
+/// and center frequency.
+///
+/// The file is created, or truncated if it exists.
+/// See [`CaptureInfo`] for the file format.
+///
+/// # Example
+///
+/// ```no_run
+/// use hackrfone::{write_cs8, HackRfOne, RxMode, UnknownMode};
+///
+/// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+/// radio.set_sample_rate(20_000_000, 2)?;
+/// radio.set_freq(915_000_000)?;
+/// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+/// let samples: Vec<u8> = radio.rx()?;
+/// radio.stop_rx()?;
+///
+/// write_cs8("capture.cs8", &samples, 10_000_000, 915_000_000)?;
+/// # Ok::<(), hackrfone::Error>(())
+/// ```
+pub fn write_cs8<P: AsRef<Path>>(
+    path: P,
+    samples: &[u8],
+    sample_rate: u32,
+    center_freq: u64,
+) -> Result<(), Error> {
+    let info: CaptureInfo = CaptureInfo {
+        sample_rate,
+        center_freq,
+    };
+    let mut file: BufWriter<File> = BufWriter::new(File::create(path)?);
+    file.write_all(&info.to_bytes())?;
+    file.write_all(samples)?;
+    file.flush()?;
+    Ok(())
+}
+
+/// Read a capture file written by [`write_cs8`].
+///
+/// Returns the metadata and the samples as interleaved signed 8-bit IQ.
+///
+/// # Errors
+///
+/// Returns `Error::Io(ErrorKind::InvalidData)` if the header is not a
+/// capture file header, or has a version newer than this crate supports.
+///
+/// # Example
+///
+/// ```no_run
+/// use hackrfone::{read_cs8, CaptureInfo};
+///
+/// let (info, samples): (CaptureInfo, Vec<u8>) = read_cs8("capture.cs8")?;
+/// println!("{} samples at {} Hz", samples.len() / 2, info.center_freq);
+/// # Ok::<(), hackrfone::Error>(())
+/// ```
+pub fn read_cs8<P: AsRef<Path>>(path: P) -> Result<(CaptureInfo, Vec<u8>), Error> {
+    let mut file: File = File::open(path)?;
+    let mut header: [u8; CS8_HEADER_SIZE] = [0; CS8_HEADER_SIZE];
+    file.read_exact(&mut header)?;
+    let info: CaptureInfo = CaptureInfo::from_bytes(&header)?;
+    let mut samples: Vec<u8> = Vec::new();
+    file.read_to_end(&mut samples)?;
+    Ok((info, samples))
+}
+
+#[cfg(test)]
+mod capture_info {
+    use super::{CaptureInfo, CS8_HEADER_SIZE};
+    use crate::Error;
+    use std::io::ErrorKind;
+
+    const INFO: CaptureInfo = CaptureInfo {
+        sample_rate: 10_000_000,
+        center_freq: 5_800_000_000,
+    };
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(CaptureInfo::from_bytes(&INFO.to_bytes()), Ok(INFO));
+    }
+
+    #[test]
+    fn layout() {
+        let buf: [u8; CS8_HEADER_SIZE] = INFO.to_bytes();
+        assert_eq!(&buf[0..8], b"HRF\0\x01\x00\x01\x00");
+        assert_eq!(&buf[8..12], &[0x80, 0x96, 0x98, 0x00]);
+        assert_eq!(&buf[16..24], &5_800_000_000u64.to_le_bytes());
+        assert!(buf[12..16].iter().chain(&buf[24..32]).all(|&b| b == 0));
+    }
+
+    #[test]
+    fn bad_magic() {
+        let mut buf: [u8; CS8_HEADER_SIZE] = INFO.to_bytes();
+        buf[0] = b'X';
+        assert_eq!(
+            CaptureInfo::from_bytes(&buf),
+            Err(Error::Io(ErrorKind::InvalidData))
+        );
+    }
+
+    #[test]
+    fn newer_version() {
+        let mut buf: [u8; CS8_HEADER_SIZE] = INFO.to_bytes();
+        buf[4] = 2;
+        assert_eq!(
+            CaptureInfo::from_bytes(&buf),
+            Err(Error::Io(ErrorKind::InvalidData))
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{Error, HackRfOne, RxMode, TxMode, UnknownMode};
+
+/// Runtime transceiver mode of a [`HackRfOneDyn`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DynMode {
+    /// Not streaming.
+    Idle,
+    /// Receiving.
+    Rx,
+    /// Transmitting.
+    Tx,
+}
+
+enum Radio {
+    Idle(HackRfOne<UnknownMode>),
+    Rx(HackRfOne<RxMode>),
+    Tx(HackRfOne<TxMode>),
+}
+
+/// Call a method on the radio regardless of mode.
+macro_rules! with_radio {
+    ($self:ident, $radio:ident => $e:expr) => {
+        match $self.radio_mut()? {
+            Radio::Idle($radio) => $e,
+            Radio::Rx($radio) => $e,
+            Radio::Tx($radio) => $e,
+        }
+    };
+}
+
+/// HackRF One with the mode checked at runtime.
+///
+/// [`HackRfOne`] tracks the transceiver mode in the type, which does not
+/// translate to C FFI or dynamic languages.
+/// This wraps it behind a single type, mode changes are plain methods and
+/// operations that are invalid in the current mode return
+/// [`Error::Mode`].
+///
+/// Pure Rust users should prefer [`HackRfOne`], which rejects invalid mode
+/// transitions at compile time.
+///
+/// If a mode change fails the radio is dropped, after that all methods
+/// return `Error::Usb(rusb::Error::NoDevice)`.
+///
+/// # Example
+///
+/// ```no_run
+/// use hackrfone::{DynMode, HackRfOneDyn};
+///
+/// let mut radio: HackRfOneDyn = HackRfOneDyn::new()?;
+/// radio.set_freq(915_000_000)?;
+/// radio.start_rx()?;
+/// assert_eq!(radio.mode(), Some(DynMode::Rx));
+/// let data: Vec<u8> = radio.rx()?;
+/// radio.stop_rx()?;
+/// # Ok::<(), hackrfone::Error>(())
+/// ```
+pub struct HackRfOneDyn {
+    radio: Option<Radio>,
+}
+
+impl From<HackRfOne<UnknownMode>> for HackRfOneDyn {
+    fn from(radio: HackRfOne<UnknownMode>) -> Self {
+        HackRfOneDyn {
+            radio: Some(Radio::Idle(radio)),
+        }
+    }
+}
+
+impl HackRfOneDyn {
+    /// Open a new HackRF One.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Usb(rusb::Error::NoDevice)` if no radio was found.
+    pub fn new() -> Result<HackRfOneDyn, Error> {
+        HackRfOne::new()
+            .map(HackRfOneDyn::from)
+            .ok_or(Error::Usb(rusb::Error::NoDevice))
+    }
+
+    /// Current mode, `None` if the radio was lost in a failed mode change.
+    pub fn mode(&self) -> Option<DynMode> {
+        self.radio.as_ref().map(|radio| match radio {
+            Radio::Idle(_) => DynMode::Idle,
+            Radio::Rx(_) => DynMode::Rx,
+            Radio::Tx(_) => DynMode::Tx,
+        })
+    }
+
+    fn radio_mut(&mut self) -> Result<&mut Radio, Error> {
+        self.radio.as_mut().ok_or(Error::Usb(rusb::Error::NoDevice))
+    }
+
+    /// Error for an operation that is invalid in the current mode.
+    fn mode_error(&self) -> Error {
+        match self.radio {
+            Some(_) => Error::Mode,
+            None => Error::Usb(rusb::Error::NoDevice),
+        }
+    }
+
+    /// Start receiving, see [`HackRfOne::into_rx_mode`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Mode`] if the radio is not idle.
+    pub fn start_rx(&mut self) -> Result<(), Error> {
+        match self.radio.take() {
+            Some(Radio::Idle(radio)) => {
+                self.radio = Some(Radio::Rx(radio.into_rx_mode()?));
+                Ok(())
+            }
+            radio => {
+                self.radio = radio;
+                Err(self.mode_error())
+            }
+        }
+    }
+
+    /// Stop receiving, see [`HackRfOne::stop_rx`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Mode`] if the radio is not receiving.
+    pub fn stop_rx(&mut self) -> Result<(), Error> {
+        match self.radio.take() {
+            Some(Radio::Rx(radio)) => {
+                self.radio = Some(Radio::Idle(radio.stop_rx()?));
+                Ok(())
+            }
+            radio => {
+                self.radio = radio;
+                Err(self.mode_error())
+            }
+        }
+    }
+
+    /// Start transmitting, see [`HackRfOne::into_tx_mode`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Mode`] if the radio is not idle.
+    pub fn start_tx(&mut self) -> Result<(), Error> {
+        match self.radio.take() {
+            Some(Radio::Idle(radio)) => {
+                self.radio = Some(Radio::Tx(radio.into_tx_mode()?));
+                Ok(())
+            }
+            radio => {
+                self.radio = radio;
+                Err(self.mode_error())
+            }
+        }
+    }
+
+    /// Stop transmitting, see [`HackRfOne::stop_tx`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Mode`] if the radio is not transmitting.
+    pub fn stop_tx(&mut self) -> Result<(), Error> {
+        match self.radio.take() {
+            Some(Radio::Tx(radio)) => {
+                self.radio = Some(Radio::Idle(radio.stop_tx()?));
+                Ok(())
+            }
+            radio => {
+                self.radio = radio;
+                Err(self.mode_error())
+            }
+        }
+    }
+
+    /// Receive data, see [`HackRfOne::rx`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Mode`] if the radio is not receiving.
+    pub fn rx(&mut self) -> Result<Vec<u8>, Error> {
+        match self.radio_mut()? {
+            Radio::Rx(radio) => radio.rx(),
+            _ => Err(Error::Mode),
+        }
+    }
+
+    /// Receive data into a buffer, see [`HackRfOne::rx_into`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Mode`] if the radio is not receiving.
+    pub fn rx_into(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        match self.radio_mut()? {
+            Radio::Rx(radio) => radio.rx_into(buf),
+            _ => Err(Error::Mode),
+        }
+    }
+
+    /// Transmit data, see [`HackRfOne::tx`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Mode`] if the radio is not transmitting.
+    pub fn tx(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        match self.radio_mut()? {
+            Radio::Tx(radio) => radio.tx(buf),
+            _ => Err(Error::Mode),
+        }
+    }
+
+    /// Set the center frequency, see [`HackRfOne::set_freq`].
+    pub fn set_freq(&mut self, hz: u64) -> Result<(), Error> {
+        with_radio!(self, radio => radio.set_freq(hz))
+    }
+
+    /// Set the sample rate, see [`HackRfOne::set_sample_rate`].
+    pub fn set_sample_rate(&mut self, hz: u32, div: u32) -> Result<(), Error> {
+        with_radio!(self, radio => radio.set_sample_rate(hz, div))
+    }
+
+    /// Set the baseband filter bandwidth, see
+    /// [`HackRfOne::set_baseband_filter_bandwidth`].
+    pub fn set_baseband_filter_bandwidth(&mut self, hz: u32) -> Result<u32, Error> {
+        with_radio!(self, radio => radio.set_baseband_filter_bandwidth(hz))
+    }
+
+    /// Enable the RF amplifier, see [`HackRfOne::set_amp_enable`].
+    pub fn set_amp_enable(&mut self, en: bool) -> Result<(), Error> {
+        with_radio!(self, radio => radio.set_amp_enable(en))
+    }
+
+    /// Set the LNA gain, see [`HackRfOne::set_lna_gain`].
+    pub fn set_lna_gain(&mut self, gain: u16) -> Result<u16, Error> {
+        with_radio!(self, radio => radio.set_lna_gain(gain))
+    }
+
+    /// Set the VGA gain, see [`HackRfOne::set_vga_gain`].
+    pub fn set_vga_gain(&mut self, gain: u16) -> Result<u16, Error> {
+        with_radio!(self, radio => radio.set_vga_gain(gain))
+    }
+
+    /// Set the transmit VGA gain, see [`HackRfOne::set_txvga_gain`].
+    pub fn set_txvga_gain(&mut self, gain: u16) -> Result<u16, Error> {
+        with_radio!(self, radio => radio.set_txvga_gain(gain))
+    }
+}
@@ -0,0 +1,109 @@
+use num_complex::Complex;
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+/// Size of a `gr_complex` in bytes.
+const GR_COMPLEX_SIZE: usize = 8;
+
+/// Write samples to a file in the GNU Radio `gr_complex` format.
+///
+/// The file contains interleaved I and Q as 32-bit floats in native byte
+/// order with no header, as read by the GNU Radio `file_source` block with
+/// the complex output type.
+/// Files written on a little endian host can not be read by GNU Radio on a
+/// big endian host.
+///
+/// The file is created, or truncated if it exists.
+///
+/// # Example
+///
+/// ```no_run
+/// use hackrfone::{samples_to_cplx_f32, write_grc_complex, HackRfOne, RxMode, UnknownMode};
+///
+/// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+/// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+/// let samples = samples_to_cplx_f32(&radio.rx()?);
+/// radio.stop_rx()?;
+///
+/// write_grc_complex("capture.cfile", &samples)?;
+/// # Ok::<(), hackrfone::Error>(())
+/// ```
+pub fn write_grc_complex<P: AsRef<Path>>(path: P, samples: &[Complex<f32>]) -> io::Result<()> {
+    fs::write(path, to_bytes(samples))
+}
+
+/// Read a file in the GNU Radio `gr_complex` format.
+///
+/// See [`write_grc_complex`] for the file format.
+///
+/// # Errors
+///
+/// Returns an error of kind [`ErrorKind::InvalidData`] if the file size is
+/// not a whole number of samples.
+///
+/// # Example
+///
+/// ```no_run
+/// use hackrfone::{num_complex::Complex, read_grc_complex};
+///
+/// let samples: Vec<Complex<f32>> = read_grc_complex("capture.cfile")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn read_grc_complex<P: AsRef<Path>>(path: P) -> io::Result<Vec<Complex<f32>>> {
+    from_bytes(&fs::read(path)?).ok_or_else(|| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            "file size is not a whole number of gr_complex samples",
+        )
+    })
+}
+
+fn to_bytes(samples: &[Complex<f32>]) -> Vec<u8> {
+    let mut buf: Vec<u8> = Vec::with_capacity(samples.len() * GR_COMPLEX_SIZE);
+    samples.iter().for_each(|sample| {
+        buf.extend_from_slice(&sample.re.to_ne_bytes());
+        buf.extend_from_slice(&sample.im.to_ne_bytes());
+    });
+    buf
+}
+
+fn from_bytes(buf: &[u8]) -> Option<Vec<Complex<f32>>> {
+    if !buf.len().is_multiple_of(GR_COMPLEX_SIZE) {
+        return None;
+    }
+    Some(
+        buf.chunks_exact(GR_COMPLEX_SIZE)
+            .map(|b| {
+                Complex::new(
+                    f32::from_ne_bytes([b[0], b[1], b[2], b[3]]),
+                    f32::from_ne_bytes([b[4], b[5], b[6], b[7]]),
+                )
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod gr_complex {
+    use super::{from_bytes, to_bytes};
+    use num_complex::Complex;
+
+    #[test]
+    fn round_trip() {
+        let samples: [Complex<f32>; 2] = [Complex::new(1.0, -0.5), Complex::new(0.25, 0.0)];
+        let buf: Vec<u8> = to_bytes(&samples);
+        assert_eq!(buf.len(), 16);
+        assert_eq!(&buf[0..4], &1.0_f32.to_ne_bytes());
+        assert_eq!(&buf[4..8], &(-0.5_f32).to_ne_bytes());
+        assert_eq!(from_bytes(&buf).unwrap(), samples);
+    }
+
+    #[test]
+    fn partial_sample() {
+        assert_eq!(from_bytes(&[0; 12]), None);
+        assert_eq!(from_bytes(&[]), Some(Vec::new()));
+    }
+}
@@ -7,17 +7,156 @@
 pub use rusb;
 
 use rusb::{request_type, Direction, GlobalContext, Recipient, RequestType, UsbContext, Version};
-use std::time::Duration;
+use std::{
+    fs::File,
+    io::Read,
+    path::Path,
+    sync::OnceLock,
+    thread,
+    time::{Duration, Instant},
+};
 
 #[cfg(feature = "num-complex")]
 pub use num_complex;
 
+mod aligned_buf;
+mod cs8;
+mod dyn_radio;
+#[cfg(feature = "num-complex")]
+mod gnuradio;
+mod recorder;
+mod ring_capture;
+mod rx_stream;
+#[cfg(feature = "soapy")]
+mod soapy;
+mod trace;
+
+pub use cs8::{read_cs8, write_cs8, CaptureInfo, CS8_HEADER_SIZE};
+pub use dyn_radio::{DynMode, HackRfOneDyn};
+#[cfg(feature = "num-complex")]
+pub use gnuradio::{read_grc_complex, write_grc_complex};
+pub use recorder::{Recorder, SampleFormat};
+pub use ring_capture::RingCapture;
+pub use rx_stream::RxStream;
+#[cfg(feature = "soapy")]
+pub use soapy::SoapyStream;
+
 /// HackRF USB vendor ID.
 const HACKRF_USB_VID: u16 = 0x1D50;
 /// HackRF One USB product ID.
 const HACKRF_ONE_USB_PID: u16 = 0x6089;
 
+/// Default size of a single RX bulk transfer in bytes.
+///
+/// This is the amount of data returned by one call to [`HackRfOne::rx`],
+/// unless [low latency mode](HackRfOne::set_low_latency) is enabled.
+pub const RX_MTU_DEFAULT: usize = 128 * 1024;
+
+/// Size of a single RX bulk transfer in bytes in low latency mode.
+///
+/// See [`HackRfOne::set_low_latency`].
+pub const RX_MTU_LOW_LATENCY: usize = 4 * 1024;
+
+/// Default timeout for USB transfers.
+const TIMEOUT_DEFAULT: Duration = Duration::from_secs(1);
+/// Timeout for USB transfers in low latency mode.
+const TIMEOUT_LOW_LATENCY: Duration = Duration::from_millis(100);
+
+/// USB bulk IN endpoint used for RX samples.
+pub const RX_ENDPOINT: u8 = 0x81;
+
+/// Baseband filter bandwidths supported by the MAX2837 transceiver in Hz.
+pub const BASEBAND_FILTER_BW: [u32; 16] = [
+    1_750_000, 2_500_000, 3_500_000, 5_000_000, 5_500_000, 6_000_000, 7_000_000, 8_000_000,
+    9_000_000, 10_000_000, 12_000_000, 14_000_000, 15_000_000, 20_000_000, 24_000_000, 28_000_000,
+];
+
+/// USB bulk OUT endpoint used for TX samples.
+pub const TX_ENDPOINT: u8 = 0x02;
+
+/// Default number of queued transfers for an [`RxStream`].
+///
+/// This matches `libhackrf`.
+pub const TRANSFER_COUNT_DEFAULT: usize = 4;
+
+/// Default size of a single [`RxStream`] transfer in bytes.
+///
+/// This matches `libhackrf`.
+pub const TRANSFER_SIZE_DEFAULT: usize = 256 * 1024;
+
+/// Number of MAX2837 transceiver registers.
+pub const MAX2837_NUM_REGISTERS: usize = 32;
+
+/// MAX2837 register values written by the firmware at power on.
+///
+/// Used by [`HackRfOne::reset_max2837`].
+pub const MAX2837_DEFAULT_REGISTERS: [u16; MAX2837_NUM_REGISTERS] = [
+    0x150, 0x002, 0x1f4, 0x1b9, 0x00a, 0x080, 0x006, 0x000, 0x080, 0x018, 0x058, 0x016, 0x24f,
+    0x150, 0x1c5, 0x081, 0x01c, 0x155, 0x155, 0x153, 0x241, 0x02d, 0x1a9, 0x24f, 0x180, 0x100,
+    0x3ca, 0x3e3, 0x0c0, 0x3f0, 0x080, 0x000,
+];
+
+/// Number of Si5351C clock generator registers.
+pub const SI5351C_NUM_REGISTERS: usize = 256;
+
+/// Number of RFFC5071 mixer registers.
+pub const RFFC5071_NUM_REGISTERS: usize = 31;
+
+/// Lowest frequency in the specified tuning range of the HackRF One in Hz.
+pub const FREQ_MIN_HZ: u64 = 1_000_000;
+
+/// Highest frequency in the specified tuning range of the HackRF One in Hz.
+pub const FREQ_MAX_HZ: u64 = 6_000_000_000;
+
+/// Lowest recommended sample rate in Hz.
+///
+/// The MAX5864 ADC is not specified below 2 MS/s.
+pub const SAMPLE_RATE_MIN_HZ: u32 = 2_000_000;
+
+/// Highest sample rate of the HackRF One in Hz.
+///
+/// See [`HackRfOne::max_sample_rate`] for the maximum of a connected board.
+pub const SAMPLE_RATE_MAX_HZ: u32 = 20_000_000;
+
+/// USB high-speed bulk packet size in bytes.
+///
+/// Bulk transfers are made up of packets of this size.
+pub const USB_PACKET_SIZE: usize = 512;
+
+/// Size of a sweep block in bytes.
+///
+/// Each block starts with a header containing the frequency the block was
+/// received at.
+pub const SWEEP_BLOCK_SIZE: usize = 16384;
+
+/// Size of the sweep block header in bytes.
+pub const SWEEP_HEADER_SIZE: usize = 10;
+
+/// Maximum number of frequency ranges in a sweep.
+pub const SWEEP_MAX_RANGES: usize = 10;
+
+/// SPI flash page size in bytes.
+///
+/// This is the largest amount of data that can be read or written in a single
+/// SPI flash control transfer.
+pub const SPIFLASH_PAGE_SIZE: usize = 256;
+
+/// SPI flash size in bytes.
+///
+/// The HackRF One uses a 1 MiB W25Q80BV SPI flash for firmware storage.
+pub const SPIFLASH_SIZE: usize = 1024 * 1024;
+
+// Minimum USB API versions for requests that not all firmware supports.
+const API_CLKOUT: Version = Version(1, 0, 3);
+const API_HW_SYNC: Version = Version(1, 0, 2);
+const API_OPERACAKE: Version = Version(1, 0, 2);
+const API_RESET: Version = Version(1, 0, 2);
+const API_STATUS: Version = Version(1, 0, 6);
+const API_SWEEP: Version = Version(1, 0, 2);
+const API_UI: Version = Version(1, 0, 4);
+
 #[allow(dead_code)]
+#[derive(Debug, Copy, Clone)]
 #[repr(u8)]
 enum Request {
     SetTransceiverMode = 1,
@@ -55,6 +194,51 @@ enum Request {
     OperacakeGpioTest = 35,
     CpldChecksum = 36,
     UiEnable = 37,
+    GetM0State = 41,
+}
+
+#[cfg(feature = "log")]
+impl Request {
+    fn name(self) -> &'static str {
+        match self {
+            Request::SetTransceiverMode => "SetTransceiverMode",
+            Request::Max2837Write => "Max2837Write",
+            Request::Max2837Read => "Max2837Read",
+            Request::Si5351CWrite => "Si5351CWrite",
+            Request::Si5351CRead => "Si5351CRead",
+            Request::SampleRateSet => "SampleRateSet",
+            Request::BasebandFilterBandwidthSet => "BasebandFilterBandwidthSet",
+            Request::Rffc5071Write => "Rffc5071Write",
+            Request::Rffc5071Read => "Rffc5071Read",
+            Request::SpiflashErase => "SpiflashErase",
+            Request::SpiflashWrite => "SpiflashWrite",
+            Request::SpiflashRead => "SpiflashRead",
+            Request::BoardIdRead => "BoardIdRead",
+            Request::VersionStringRead => "VersionStringRead",
+            Request::SetFreq => "SetFreq",
+            Request::AmpEnable => "AmpEnable",
+            Request::BoardPartidSerialnoRead => "BoardPartidSerialnoRead",
+            Request::SetLnaGain => "SetLnaGain",
+            Request::SetVgaGain => "SetVgaGain",
+            Request::SetTxvgaGain => "SetTxvgaGain",
+            Request::AntennaEnable => "AntennaEnable",
+            Request::SetFreqExplicit => "SetFreqExplicit",
+            Request::UsbWcidVendorReq => "UsbWcidVendorReq",
+            Request::InitSweep => "InitSweep",
+            Request::OperacakeGetBoards => "OperacakeGetBoards",
+            Request::OperacakeSetPorts => "OperacakeSetPorts",
+            Request::SetHwSyncMode => "SetHwSyncMode",
+            Request::Reset => "Reset",
+            Request::OperacakeSetRanges => "OperacakeSetRanges",
+            Request::ClkoutEnable => "ClkoutEnable",
+            Request::SpiflashStatus => "SpiflashStatus",
+            Request::SpiflashClearStatus => "SpiflashClearStatus",
+            Request::OperacakeGpioTest => "OperacakeGpioTest",
+            Request::CpldChecksum => "CpldChecksum",
+            Request::UiEnable => "UiEnable",
+            Request::GetM0State => "GetM0State",
+        }
+    }
 }
 
 impl From<Request> for u8 {
@@ -64,6 +248,7 @@ impl From<Request> for u8 {
 }
 
 #[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(u8)]
 enum TranscieverMode {
     Off = 0,
@@ -86,6 +271,17 @@ impl From<TranscieverMode> for u16 {
     }
 }
 
+impl TranscieverMode {
+    /// Bulk endpoint used to stream samples in this mode.
+    fn endpoint(self) -> Option<u8> {
+        match self {
+            TranscieverMode::Receive | TranscieverMode::RxSweep => Some(RX_ENDPOINT),
+            TranscieverMode::Transmit => Some(TX_ENDPOINT),
+            _ => None,
+        }
+    }
+}
+
 /// HackRF One errors.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Error {
@@ -111,6 +307,29 @@ pub enum Error {
     },
     /// A provided argument was out of range.
     Argument,
+    /// File I/O error.
+    Io(std::io::ErrorKind),
+    /// The radio settings are inconsistent with each other.
+    Config,
+    /// The operation is not valid in the current transceiver mode.
+    Mode,
+    /// The USB link is too slow for the configured sample rate.
+    InsufficientBandwidth {
+        /// Negotiated USB speed.
+        speed: rusb::Speed,
+    },
+    /// A frequency in a scan plan was out of range.
+    ScanFrequency {
+        /// Index of the first frequency out of range.
+        index: usize,
+        /// The frequency in Hz.
+        hz: u64,
+    },
+    /// The sample rate of a scan plan was out of range.
+    ScanSampleRate {
+        /// The sample rate in Hz.
+        hz: u32,
+    },
 }
 
 impl From<rusb::Error> for Error {
@@ -119,6 +338,12 @@ impl From<rusb::Error> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e.kind())
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self)
@@ -127,107 +352,557 @@ impl std::fmt::Display for Error {
 
 impl std::error::Error for Error {}
 
-/// Typestate for RX mode.
-#[derive(Debug)]
-pub struct RxMode;
-
-/// Typestate for an unknown mode.
-#[derive(Debug)]
-pub struct UnknownMode;
+/// HackRF board variants.
+///
+/// The HackRF One shares firmware with several related boards, the variant
+/// is reported by the board ID.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Variant {
+    /// Jellybean, an early prototype.
+    Jellybean,
+    /// Jawbreaker, the beta test hardware.
+    Jawbreaker,
+    /// HackRF One prior to r9.
+    HackRfOne,
+    /// rad1o, the CCCamp 2015 badge.
+    Rad1o,
+    /// HackRF One r9.
+    HackRfOneR9,
+    /// Unrecognized board ID.
+    Unknown(u8),
+}
 
-/// HackRF One software defined radio.
-pub struct HackRfOne<MODE> {
-    dh: rusb::DeviceHandle<GlobalContext>,
-    desc: rusb::DeviceDescriptor,
-    #[allow(dead_code)]
-    mode: MODE,
-    to: Duration,
+impl From<u8> for Variant {
+    fn from(board_id: u8) -> Self {
+        match board_id {
+            0 => Variant::Jellybean,
+            1 => Variant::Jawbreaker,
+            2 => Variant::HackRfOne,
+            3 => Variant::Rad1o,
+            4 => Variant::HackRfOneR9,
+            x => Variant::Unknown(x),
+        }
+    }
 }
 
-impl HackRfOne<UnknownMode> {
-    /// Open a new HackRF One.
+impl Variant {
+    /// Maximum supported sample rate in Hz.
+    ///
+    /// All boards supported by the firmware use the MAX5864 ADC/DAC with a
+    /// 20 MS/s ceiling; unknown boards are assumed to share it.
     ///
     /// # Example
     ///
-    /// ```no_run
-    /// use hackrfone::{HackRfOne, UnknownMode};
+    /// ```
+    /// use hackrfone::Variant;
     ///
-    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// assert_eq!(Variant::HackRfOne.max_sample_rate(), 20_000_000);
     /// ```
-    pub fn new() -> Option<HackRfOne<UnknownMode>> {
-        let ctx: GlobalContext = GlobalContext {};
-        let devices = match ctx.devices() {
-            Ok(d) => d,
-            Err(_) => return None,
+    pub const fn max_sample_rate(&self) -> u32 {
+        match self {
+            Variant::Jellybean
+            | Variant::Jawbreaker
+            | Variant::HackRfOne
+            | Variant::Rad1o
+            | Variant::HackRfOneR9
+            | Variant::Unknown(_) => SAMPLE_RATE_MAX_HZ,
+        }
+    }
+}
+
+/// Details of a connected device.
+///
+/// Returned by [`HackRfOne::list_detailed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// USB bus number.
+    pub bus_number: u8,
+    /// USB device address on the bus.
+    pub address: u8,
+    /// Board ID, see [`HackRfOne::board_id`].
+    pub board_id: Option<u8>,
+    /// Firmware version string, see [`HackRfOne::version`].
+    pub version: Option<String>,
+    /// Serial number, see [`HackRfOne::serial_number`].
+    pub serial_number: Option<[u32; 4]>,
+    /// First error opening or reading the device.
+    pub error: Option<Error>,
+}
+
+/// Register values of the RF ICs.
+///
+/// Returned by [`HackRfOne::dump_registers`].
+/// The `Display` implementation prints one register per line, for pasting
+/// into bug reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterDump {
+    /// MAX2837 transceiver registers.
+    pub max2837: [u16; MAX2837_NUM_REGISTERS],
+    /// Si5351C clock generator registers.
+    pub si5351c: [u8; SI5351C_NUM_REGISTERS],
+    /// RFFC5071 mixer registers.
+    pub rffc5071: [u16; RFFC5071_NUM_REGISTERS],
+}
+
+impl std::fmt::Display for RegisterDump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (register, value) in self.max2837.iter().enumerate() {
+            writeln!(f, "MAX2837 0x{register:02X}: 0x{value:03X}")?;
+        }
+        for (register, value) in self.si5351c.iter().enumerate() {
+            writeln!(f, "Si5351C 0x{register:02X}: 0x{value:02X}")?;
+        }
+        for (register, value) in self.rffc5071.iter().enumerate() {
+            writeln!(f, "RFFC5071 0x{register:02X}: 0x{value:04X}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Firmware build information.
+///
+/// Returned by [`HackRfOne::firmware_build_info`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FirmwareBuildInfo {
+    /// Release version, such as `2021.03.1`, `None` for development builds.
+    pub release: Option<String>,
+    /// Abbreviated git commit hash, `None` for release builds.
+    pub git_hash: Option<String>,
+    /// The firmware was built from a tree with uncommitted changes.
+    pub dirty: bool,
+}
+
+impl FirmwareBuildInfo {
+    fn parse(version: &str) -> FirmwareBuildInfo {
+        let (version, dirty): (&str, bool) = match version.strip_suffix("-dirty") {
+            Some(version) => (version, true),
+            None => (version, false),
         };
+        let (release, git_hash): (&str, Option<&str>) = match version.find("git-") {
+            Some(idx) => (
+                version[..idx].trim_end_matches(['+', '-']),
+                Some(&version[idx + 4..]),
+            ),
+            None => (version, None),
+        };
+        FirmwareBuildInfo {
+            release: Some(release).filter(|r| !r.is_empty()).map(String::from),
+            git_hash: git_hash.filter(|h| !h.is_empty()).map(String::from),
+            dirty,
+        }
+    }
+}
 
-        for device in devices.iter() {
-            let desc = match device.device_descriptor() {
-                Ok(d) => d,
-                Err(_) => continue,
-            };
+/// Streaming status reported by the firmware.
+///
+/// Returned by [`HackRfOne::read_status`].
+///
+/// The status is kept by the M0 core of the LPC4320, which moves samples
+/// between the CPLD and the USB buffer.
+/// A shortfall is an RX overrun or a TX underrun, the USB host did not keep
+/// up and samples were dropped or zero samples were transmitted.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct DeviceStatus {
+    /// Active M0 mode; 0 idle, 1 waiting, 2 RX, 3 TX start, 4 TX.
+    pub mode: u32,
+    /// Number of bytes moved by the M0.
+    pub m0_count: u32,
+    /// Number of bytes moved over USB.
+    pub m4_count: u32,
+    /// Number of shortfalls since the last [`HackRfOne::clear_status`].
+    pub num_shortfalls: u32,
+    /// Length of the longest shortfall in bytes.
+    pub longest_shortfall: u32,
+    /// Error code; 0 none, 1 RX timeout, 2 TX timeout.
+    pub error: u32,
+}
 
-            if desc.vendor_id() == HACKRF_USB_VID && desc.product_id() == HACKRF_ONE_USB_PID {
-                match device.open() {
-                    Ok(handle) => {
-                        return Some(HackRfOne {
-                            dh: handle,
-                            desc,
-                            mode: UnknownMode,
-                            to: Duration::from_secs(1),
-                        })
-                    }
-                    Err(_) => continue,
-                }
-            }
+/// Size of the firmware M0 state in bytes.
+const M0_STATE_SIZE: usize = 40;
+
+impl DeviceStatus {
+    fn parse(buf: &[u8; M0_STATE_SIZE]) -> DeviceStatus {
+        let word = |offset: usize| -> u32 {
+            u32::from_le_bytes(buf[offset..offset + 4].try_into().unwrap())
+        };
+        DeviceStatus {
+            mode: word(4),
+            m0_count: word(8),
+            m4_count: word(12),
+            num_shortfalls: word(16),
+            longest_shortfall: word(20),
+            error: word(36),
         }
+    }
+}
 
-        None
+/// Optional features supported by the firmware.
+///
+/// Returned by [`HackRfOne::capabilities`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Capabilities {
+    /// Frequency sweeping.
+    pub sweep: bool,
+    /// Opera Cake antenna switching.
+    pub operacake: bool,
+    /// CLKOUT control.
+    pub clkout: bool,
+    /// UI (LCD and LEDs) control.
+    pub ui: bool,
+    /// Hardware synchronization.
+    pub hw_sync: bool,
+}
+
+/// Opera Cake antenna switch port.
+///
+/// Each board has two common ports, A0 and B0, each switched to one of the
+/// ports on its side, or to one of the ports on the other side.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum OperacakePort {
+    /// Port A1.
+    A1 = 0,
+    /// Port A2.
+    A2 = 1,
+    /// Port A3.
+    A3 = 2,
+    /// Port A4.
+    A4 = 3,
+    /// Port B1.
+    B1 = 4,
+    /// Port B2.
+    B2 = 5,
+    /// Port B3.
+    B3 = 6,
+    /// Port B4.
+    B4 = 7,
+}
+
+impl OperacakePort {
+    /// All ports.
+    pub const ALL: [OperacakePort; 8] = [
+        OperacakePort::A1,
+        OperacakePort::A2,
+        OperacakePort::A3,
+        OperacakePort::A4,
+        OperacakePort::B1,
+        OperacakePort::B2,
+        OperacakePort::B3,
+        OperacakePort::B4,
+    ];
+
+    const fn is_a(self) -> bool {
+        (self as u8) < (OperacakePort::B1 as u8)
     }
 }
 
-impl<MODE> HackRfOne<MODE> {
-    fn read_control<const N: usize>(
-        &self,
-        request: Request,
-        value: u16,
-        index: u16,
-    ) -> Result<[u8; N], Error> {
-        let mut buf: [u8; N] = [0; N];
-        let n: usize = self.dh.read_control(
-            request_type(Direction::In, RequestType::Vendor, Recipient::Device),
-            request.into(),
-            value,
-            index,
-            &mut buf,
-            self.to,
-        )?;
-        if n != buf.len() {
-            Err(Error::CtrlTransfer {
-                dir: Direction::In,
-                actual: n,
-                expected: buf.len(),
-            })
+/// Power offsets of Opera Cake antenna ports.
+///
+/// Cables, filters, and amplifiers differ per port; the offset of a port is
+/// added to power measured through it, so measurements are comparable
+/// across ports.
+/// Offsets default to 0 dB.
+///
+/// # Example
+///
+/// ```
+/// use hackrfone::{OperacakePort, PortCalibration};
+///
+/// let mut cal: PortCalibration = PortCalibration::default();
+/// // 1.5 dB of cable loss on port A2
+/// cal.set(OperacakePort::A2, 1.5);
+/// assert_eq!(cal.apply(Some(OperacakePort::A2), -30.0), -28.5);
+/// assert_eq!(cal.apply(Some(OperacakePort::A1), -30.0), -30.0);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct PortCalibration {
+    offsets_db: [f32; 8],
+}
+
+impl PortCalibration {
+    /// Set the offset of a port in dB.
+    pub fn set(&mut self, port: OperacakePort, offset_db: f32) {
+        self.offsets_db[port as usize] = offset_db;
+    }
+
+    /// Get the offset of a port in dB.
+    pub fn offset(&self, port: OperacakePort) -> f32 {
+        self.offsets_db[port as usize]
+    }
+
+    /// Apply the offset of `port` to a power in dB.
+    ///
+    /// The power is returned unchanged if `port` is `None`, use
+    /// [`HackRfOne::operacake_port`] for the active port.
+    pub fn apply(&self, port: Option<OperacakePort>, power_db: f32) -> f32 {
+        match port {
+            Some(port) => power_db + self.offset(port),
+            None => power_db,
+        }
+    }
+
+    /// Measure the power of samples received through `port` in dBFS, with
+    /// the offset of the port applied.
+    ///
+    /// See [`power_dbfs`].
+    ///
+    /// [`power_dbfs`]: crate::power_dbfs
+    pub fn power_dbfs(&self, port: Option<OperacakePort>, buf: &[u8]) -> f32 {
+        self.apply(port, power_dbfs(buf))
+    }
+}
+
+/// RF path filter.
+///
+/// The HackRF One mixes the RF signal to or from the 2.15 GHz to 2.75 GHz
+/// range of the MAX2837 transceiver through one of three filter paths.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RfPath {
+    /// No mixing, the transceiver is tuned to the RF frequency directly.
+    Bypass = 0,
+    /// Low pass filter, the RF frequency is the LO minus the IF.
+    LowPass = 1,
+    /// High pass filter, the RF frequency is the IF plus the LO.
+    HighPass = 2,
+}
+
+/// Sweep tuning style.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum SweepStyle {
+    /// Step by `step_width` and receive at `offset` from each step.
+    #[default]
+    Linear = 0,
+    /// Alternate between `offset` and `step_width - offset` from each step,
+    /// giving better coverage near the filter edges.
+    Interleaved = 1,
+}
+
+/// Sweep configuration.
+///
+/// Used with [`HackRfOne::into_sweep_mode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepConfig {
+    /// Frequency ranges to sweep as `(start, stop)` pairs in MHz.
+    ///
+    /// At most [`SWEEP_MAX_RANGES`] ranges are supported.
+    pub ranges: Vec<(u16, u16)>,
+    /// Number of bytes to receive at each frequency.
+    ///
+    /// This must be a non-zero multiple of [`SWEEP_BLOCK_SIZE`].
+    pub num_bytes: u32,
+    /// Tuning step in Hz.
+    pub step_width: u32,
+    /// Frequency offset in Hz added to each step.
+    pub offset: u32,
+    /// Tuning style.
+    pub style: SweepStyle,
+    /// Antenna port power (bias-tee) state during the sweep.
+    pub bias_tee: bool,
+    /// RF amplifier state during the sweep.
+    pub amp: bool,
+}
+
+impl Default for SweepConfig {
+    /// Sweep 2.4 - 2.5 GHz with the same defaults as `hackrf_sweep`.
+    fn default() -> Self {
+        SweepConfig {
+            ranges: vec![(2400, 2500)],
+            num_bytes: SWEEP_BLOCK_SIZE as u32,
+            step_width: 20_000_000,
+            offset: 7_500_000,
+            style: SweepStyle::Interleaved,
+            bias_tee: false,
+            amp: false,
+        }
+    }
+}
+
+/// RX settings of a radio.
+///
+/// Settings that are `None` are left unchanged by
+/// [`HackRfOne::configure_rx`].
+///
+/// Obtain the settings of a radio with [`HackRfOne::current_config`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RxConfig {
+    /// Center frequency in Hz.
+    pub freq: Option<u64>,
+    /// Sample rate frequency and divider, see [`HackRfOne::set_sample_rate`].
+    pub sample_rate: Option<(u32, u32)>,
+    /// Baseband filter bandwidth in Hz.
+    pub baseband_filter_bw: Option<u32>,
+    /// LNA gain in dB.
+    pub lna_gain: Option<u16>,
+    /// VGA gain in dB.
+    pub vga_gain: Option<u16>,
+    /// RF amplifier state.
+    pub amp_enable: Option<bool>,
+}
+
+/// RX gain chain settings.
+///
+/// The constructor validates the gains and rounds them down to the hardware
+/// steps, so an `RfGain` always holds gains the hardware can apply, and can
+/// be stored, compared, and reapplied.
+///
+/// # Example
+///
+/// ```no_run
+/// use hackrfone::{HackRfOne, RfGain, UnknownMode};
+///
+/// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+/// let gain: RfGain = RfGain::new(false, 17, 21)?;
+/// assert_eq!((gain.lna_db(), gain.vga_db()), (16, 20));
+/// gain.apply(&mut radio)?;
+/// # Ok::<(), hackrfone::Error>(())
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct RfGain {
+    amp: bool,
+    lna_db: u16,
+    vga_db: u16,
+}
+
+impl RfGain {
+    /// Create a new gain chain setting.
+    ///
+    /// The LNA gain is rounded down to a multiple of 8 dB and the VGA gain
+    /// to a multiple of 2 dB, see [`HackRfOne::set_lna_gain`] and
+    /// [`HackRfOne::set_vga_gain`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if the LNA gain is above 40 dB or the VGA
+    /// gain is above 62 dB.
+    pub fn new(amp: bool, lna_db: u16, vga_db: u16) -> Result<RfGain, Error> {
+        if lna_db > 40 || vga_db > 62 {
+            Err(Error::Argument)
         } else {
-            Ok(buf)
+            Ok(RfGain {
+                amp,
+                lna_db: lna_db & !0x07,
+                vga_db: vga_db & !0b1,
+            })
         }
     }
 
+    /// RF amplifier state.
+    pub fn amp(&self) -> bool {
+        self.amp
+    }
+
+    /// LNA gain in dB.
+    pub fn lna_db(&self) -> u16 {
+        self.lna_db
+    }
+
+    /// VGA gain in dB.
+    pub fn vga_db(&self) -> u16 {
+        self.vga_db
+    }
+
+    /// Apply the gains to a radio.
+    ///
+    /// The amplifier is set first, then the LNA and VGA gains.
+    /// Returns the gains applied by the radio.
+    pub fn apply<MODE>(&self, radio: &mut HackRfOne<MODE>) -> Result<RfGain, Error> {
+        radio.set_amp_enable(self.amp)?;
+        Ok(RfGain {
+            amp: self.amp,
+            lna_db: radio.set_lna_gain(self.lna_db)?,
+            vga_db: radio.set_vga_gain(self.vga_db)?,
+        })
+    }
+}
+
+/// Sample rate, filter, and decimation chosen by
+/// [`HackRfOne::configure_channel`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ChannelPlan {
+    /// Hardware sample rate in Hz.
+    pub sample_rate: u32,
+    /// Baseband filter bandwidth in Hz.
+    pub baseband_filter_bw: u32,
+    /// Decimation factor to apply in software.
+    pub decimation: u32,
+    /// Sample rate after decimation in Hz, twice the channel bandwidth.
+    pub channel_rate: u32,
+}
+
+/// Typestate for RX mode.
+#[derive(Debug)]
+pub struct RxMode;
+
+/// Typestate for TX mode.
+#[derive(Debug)]
+pub struct TxMode;
+
+/// Typestate for sweep mode.
+#[derive(Debug)]
+pub struct SweepMode;
+
+/// Typestate for an unknown mode.
+#[derive(Debug)]
+pub struct UnknownMode;
+
+/// Settings applied to the radio through this handle.
+///
+/// The firmware does not provide readback for most settings, `None` means
+/// the setting has not been applied through this handle.
+#[derive(Debug, Clone, Default)]
+struct Cache {
+    lna_gain: Option<u16>,
+    vga_gain: Option<u16>,
+    txvga_gain: Option<u16>,
+    freq: Option<u64>,
+    /// Payload of the last `SetFreq` request.
+    freq_params: Option<[u8; 8]>,
+    /// Sample rate frequency and divider.
+    sample_rate: Option<(u32, u32)>,
+    baseband_filter_bw: Option<u32>,
+    amp_enable: Option<bool>,
+    /// Automatic amplifier control threshold.
+    amp_auto_above: Option<u64>,
+    /// Opera Cake port switched to A0.
+    operacake_port: Option<OperacakePort>,
+    /// Power offsets applied by `power_dbfs`.
+    port_calibration: PortCalibration,
+    /// Shortfall count at the last `clear_status`.
+    shortfall_baseline: Option<u32>,
+    /// Frequency correction applied by `set_freq`.
+    freq_correction_ppm: Option<f32>,
+    /// Board variant, read once on first use.
+    variant: OnceLock<Variant>,
+}
+
+/// USB device handle and the transceiver mode set through it.
+struct Device {
+    dh: rusb::DeviceHandle<GlobalContext>,
+    /// Transceiver mode set through this handle.
+    tm: TranscieverMode,
+}
+
+impl Device {
     fn write_control(
-        &mut self,
+        &self,
         request: Request,
         value: u16,
         index: u16,
         buf: &[u8],
+        to: Duration,
     ) -> Result<(), Error> {
-        let n: usize = self.dh.write_control(
+        let result: Result<usize, rusb::Error> = self.dh.write_control(
             request_type(Direction::Out, RequestType::Vendor, Recipient::Device),
             request.into(),
             value,
             index,
             buf,
-            self.to,
-        )?;
+            to,
+        );
+        trace::control(Direction::Out, request, value, index, buf.len(), &result);
+        let n: usize = result?;
         if n != buf.len() {
             Err(Error::CtrlTransfer {
                 dir: Direction::Out,
@@ -238,525 +913,4866 @@ impl<MODE> HackRfOne<MODE> {
             Ok(())
         }
     }
+}
 
-    fn check_api_version(&self, min: Version) -> Result<(), Error> {
-        fn version_to_u32(v: Version) -> u32 {
-            ((v.major() as u32) << 16) | ((v.minor() as u32) << 8) | (v.sub_minor() as u32)
-        }
-
-        let v: Version = self.device_version();
-        let v_cmp: u32 = version_to_u32(v);
-        let min_cmp: u32 = version_to_u32(min);
-
-        if v_cmp >= min_cmp {
-            Ok(())
-        } else {
-            Err(Error::Version { device: v, min })
+/// Stops transmitting when a handle in TX mode is dropped.
+///
+/// Dropping a handle mid-transmit would otherwise leave the transceiver
+/// transmitting and the amplifier keyed.
+/// This is best-effort, errors are ignored.
+impl Drop for Device {
+    fn drop(&mut self) {
+        if self.tm == TranscieverMode::Transmit {
+            let off: u16 = TranscieverMode::Off.into();
+            self.write_control(Request::SetTransceiverMode, off, 0, &[], TIMEOUT_DEFAULT)
+                .ok();
+            self.write_control(Request::AmpEnable, 0, 0, &[], TIMEOUT_DEFAULT)
+                .ok();
         }
     }
+}
 
-    /// Get the device version from the USB descriptor.
-    ///
-    /// The HackRF C API calls the equivalent of this function
-    /// `hackrf_usb_api_version_read`.
+/// HackRF One software defined radio.
+///
+/// # Thread safety
+///
+/// `HackRfOne` is `Send` and `Sync`, it can be moved into a sampling thread.
+pub struct HackRfOne<MODE> {
+    dev: Device,
+    desc: rusb::DeviceDescriptor,
+    #[allow(dead_code)]
+    mode: MODE,
+    to: Duration,
+    /// Timeout replaced by [`HackRfOne::set_low_latency`].
+    saved_to: Option<Duration>,
+    /// Size of a single [`HackRfOne::rx`] transfer.
+    rx_mtu: usize,
+    transfer_count: usize,
+    transfer_size: usize,
+    cache: Cache,
+}
+
+impl HackRfOne<UnknownMode> {
+    /// Open a new HackRF One.
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use hackrfone::{rusb, HackRfOne, UnknownMode};
+    /// use hackrfone::{HackRfOne, UnknownMode};
     ///
     /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-    /// assert_eq!(radio.device_version(), rusb::Version(1, 0, 4));
     /// ```
-    pub fn device_version(&self) -> Version {
-        self.desc.device_version()
+    pub fn new() -> Option<HackRfOne<UnknownMode>> {
+        Self::open_first(|_| true)
     }
 
-    /// Set the timeout for USB transfers.
+    /// Set the log verbosity of libusb.
+    ///
+    /// This is the programmatic equivalent of setting the `LIBUSB_DEBUG`
+    /// environment variable, and it applies to every HackRF One opened by
+    /// this crate.
     ///
     /// # Example
     ///
-    /// Set a 100ms timeout.
+    /// Enable libusb debug logging.
     ///
     /// ```no_run
-    /// use hackrfone::{HackRfOne, UnknownMode};
-    /// use std::time::Duration;
+    /// use hackrfone::{rusb, HackRfOne, UnknownMode};
     ///
+    /// HackRfOne::set_log_level(rusb::LogLevel::Debug);
     /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-    /// radio.set_timeout(Duration::from_millis(100))
     /// ```
-    pub fn set_timeout(&mut self, duration: Duration) {
-        self.to = duration;
+    pub fn set_log_level(level: rusb::LogLevel) {
+        let mut ctx: GlobalContext = GlobalContext {};
+        ctx.set_log_level(level);
     }
 
-    /// Read the board ID.
+    fn from_handle(
+        dh: rusb::DeviceHandle<GlobalContext>,
+        desc: rusb::DeviceDescriptor,
+    ) -> HackRfOne<UnknownMode> {
+        HackRfOne {
+            dev: Device {
+                dh,
+                tm: TranscieverMode::Off,
+            },
+            desc,
+            mode: UnknownMode,
+            to: TIMEOUT_DEFAULT,
+            saved_to: None,
+            rx_mtu: RX_MTU_DEFAULT,
+            transfer_count: TRANSFER_COUNT_DEFAULT,
+            transfer_size: TRANSFER_SIZE_DEFAULT,
+            cache: Cache::default(),
+        }
+    }
+
+    /// List all connected HackRF One devices with their details.
+    ///
+    /// Each device is opened briefly to read its board ID, firmware version,
+    /// and serial number.
+    /// Devices that cannot be opened or read, for example due to missing
+    /// permissions, are still listed with the error, and the details that
+    /// could be read.
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use hackrfone::{HackRfOne, UnknownMode};
+    /// use hackrfone::{DeviceInfo, HackRfOne};
     ///
-    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-    /// assert_eq!(radio.board_id()?, 0x02);
+    /// let devices: Vec<DeviceInfo> = HackRfOne::list_detailed()?;
+    /// for info in devices {
+    ///     match (info.serial_number, info.version, info.error) {
+    ///         (Some(serial), Some(version), None) => {
+    ///             println!("HackRF One, serial {serial:08x?}, fw {version}")
+    ///         }
+    ///         (_, _, error) => println!("HackRF One, unusable: {error:?}"),
+    ///     }
+    /// }
     /// # Ok::<(), hackrfone::Error>(())
     /// ```
-    pub fn board_id(&self) -> Result<u8, Error> {
-        let data: [u8; 1] = self.read_control(Request::BoardIdRead, 0, 0)?;
-        Ok(data[0])
+    pub fn list_detailed() -> Result<Vec<DeviceInfo>, Error> {
+        let ctx: GlobalContext = GlobalContext {};
+        let mut list: Vec<DeviceInfo> = Vec::new();
+
+        for device in ctx.devices()?.iter() {
+            let desc = match device.device_descriptor() {
+                Ok(d) => d,
+                Err(_) => continue,
+            };
+            if desc.vendor_id() != HACKRF_USB_VID || desc.product_id() != HACKRF_ONE_USB_PID {
+                continue;
+            }
+
+            let mut info: DeviceInfo = DeviceInfo {
+                bus_number: device.bus_number(),
+                address: device.address(),
+                board_id: None,
+                version: None,
+                serial_number: None,
+                error: None,
+            };
+            match device.open() {
+                Ok(handle) => {
+                    let radio: HackRfOne<UnknownMode> = HackRfOne::from_handle(handle, desc);
+                    let board_id: Result<u8, Error> = radio.board_id();
+                    let version: Result<String, Error> = radio.version();
+                    let serial_number: Result<[u32; 4], Error> = radio.serial_number();
+                    info.error = board_id
+                        .as_ref()
+                        .err()
+                        .or(version.as_ref().err())
+                        .or(serial_number.as_ref().err())
+                        .copied();
+                    info.board_id = board_id.ok();
+                    info.version = version.ok();
+                    info.serial_number = serial_number.ok();
+                }
+                Err(e) => info.error = Some(e.into()),
+            }
+            list.push(info);
+        }
+
+        Ok(list)
     }
 
-    /// Read the firmware version.
+    /// Open the first HackRF One accepted by `f`.
+    fn open_first<F>(mut f: F) -> Option<HackRfOne<UnknownMode>>
+    where
+        F: FnMut(&HackRfOne<UnknownMode>) -> bool,
+    {
+        let ctx: GlobalContext = GlobalContext {};
+        let devices = match ctx.devices() {
+            Ok(d) => d,
+            Err(_) => return None,
+        };
+
+        for device in devices.iter() {
+            let desc = match device.device_descriptor() {
+                Ok(d) => d,
+                Err(_) => continue,
+            };
+
+            if desc.vendor_id() == HACKRF_USB_VID && desc.product_id() == HACKRF_ONE_USB_PID {
+                match device.open() {
+                    Ok(handle) => {
+                        let radio: HackRfOne<UnknownMode> = HackRfOne::from_handle(handle, desc);
+                        if f(&radio) {
+                            return Some(radio);
+                        }
+                    }
+                    Err(_) => continue,
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl<MODE> HackRfOne<MODE> {
+    fn read_control<const N: usize>(
+        &self,
+        request: Request,
+        value: u16,
+        index: u16,
+    ) -> Result<[u8; N], Error> {
+        let mut buf: [u8; N] = [0; N];
+        self.read_control_into(request, value, index, &mut buf)?;
+        Ok(buf)
+    }
+
+    fn read_control_into(
+        &self,
+        request: Request,
+        value: u16,
+        index: u16,
+        buf: &mut [u8],
+    ) -> Result<(), Error> {
+        let result: Result<usize, rusb::Error> = self.dev.dh.read_control(
+            request_type(Direction::In, RequestType::Vendor, Recipient::Device),
+            request.into(),
+            value,
+            index,
+            buf,
+            self.to,
+        );
+        trace::control(Direction::In, request, value, index, buf.len(), &result);
+        let n: usize = result?;
+        if n != buf.len() {
+            Err(Error::CtrlTransfer {
+                dir: Direction::In,
+                actual: n,
+                expected: buf.len(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn read_bulk(&self, buf: &mut [u8]) -> Result<usize, rusb::Error> {
+        let result: Result<usize, rusb::Error> = self.dev.dh.read_bulk(RX_ENDPOINT, buf, self.to);
+        trace::bulk(RX_ENDPOINT, buf.len(), &result);
+        result
+    }
+
+    fn write_bulk(&self, buf: &[u8]) -> Result<usize, rusb::Error> {
+        let result: Result<usize, rusb::Error> = self.dev.dh.write_bulk(TX_ENDPOINT, buf, self.to);
+        trace::bulk(TX_ENDPOINT, buf.len(), &result);
+        result
+    }
+
+    fn write_control(
+        &mut self,
+        request: Request,
+        value: u16,
+        index: u16,
+        buf: &[u8],
+    ) -> Result<(), Error> {
+        self.dev.write_control(request, value, index, buf, self.to)
+    }
+
+    fn supports_api_version(&self, min: Version) -> bool {
+        fn version_to_u32(v: Version) -> u32 {
+            ((v.major() as u32) << 16) | ((v.minor() as u32) << 8) | (v.sub_minor() as u32)
+        }
+
+        version_to_u32(self.device_version()) >= version_to_u32(min)
+    }
+
+    fn check_api_version(&self, min: Version) -> Result<(), Error> {
+        if self.supports_api_version(min) {
+            Ok(())
+        } else {
+            Err(Error::Version {
+                device: self.device_version(),
+                min,
+            })
+        }
+    }
+
+    fn with_mode<NEW>(self, mode: NEW) -> HackRfOne<NEW> {
+        HackRfOne {
+            dev: self.dev,
+            desc: self.desc,
+            mode,
+            to: self.to,
+            saved_to: self.saved_to,
+            rx_mtu: self.rx_mtu,
+            transfer_count: self.transfer_count,
+            transfer_size: self.transfer_size,
+            cache: self.cache,
+        }
+    }
+
+    /// Get the device version from the USB descriptor.
+    ///
+    /// The HackRF C API calls the equivalent of this function
+    /// `hackrf_usb_api_version_read`.
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use hackrfone::{HackRfOne, UnknownMode};
+    /// use hackrfone::{rusb, HackRfOne, UnknownMode};
     ///
     /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-    /// assert_eq!(radio.version()?, "2021.03.1");
-    /// # Ok::<(), hackrfone::Error>(())
+    /// assert_eq!(radio.device_version(), rusb::Version(1, 0, 4));
     /// ```
-    pub fn version(&self) -> Result<String, Error> {
-        let mut buf: [u8; 16] = [0; 16];
-        let n: usize = self.dh.read_control(
-            request_type(Direction::In, RequestType::Vendor, Recipient::Device),
-            Request::VersionStringRead.into(),
-            0,
-            0,
-            &mut buf,
-            self.to,
-        )?;
-        Ok(String::from_utf8_lossy(&buf[0..n]).into())
+    pub fn device_version(&self) -> Version {
+        self.desc.device_version()
     }
 
-    /// Set the center frequency.
+    /// Get the timeout for USB transfers.
+    ///
+    /// A zero duration indicates there is no timeout, see
+    /// [`set_timeout_infinite`].
     ///
     /// # Example
     ///
-    /// Set the frequency to 915MHz.
+    /// Temporarily extend the timeout.
     ///
     /// ```no_run
     /// use hackrfone::{HackRfOne, UnknownMode};
+    /// use std::time::Duration;
     ///
     /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-    /// radio.set_freq(915_000_000)?;
+    /// let timeout: Duration = radio.timeout();
+    /// radio.set_timeout(Duration::from_secs(10))?;
+    /// // ... slow operation
+    /// radio.set_timeout(timeout)?;
     /// # Ok::<(), hackrfone::Error>(())
     /// ```
-    pub fn set_freq(&mut self, hz: u64) -> Result<(), Error> {
-        let buf: [u8; 8] = freq_params(hz);
-        self.write_control(Request::SetFreq, 0, 0, &buf)
+    ///
+    /// [`set_timeout_infinite`]: crate::HackRfOne::set_timeout_infinite
+    pub fn timeout(&self) -> Duration {
+        self.to
     }
 
-    /// Enable the RX/TX RF amplifier.
+    /// Get the optional features supported by the firmware.
     ///
-    /// In GNU radio this is used as the RF gain, where a value of 0 dB is off,
-    /// and a value of 14 dB is on.
+    /// This is determined from the device version and does not perform any
+    /// USB transfers.
+    /// It does not detect accessories, an Opera Cake may be supported but
+    /// not connected.
     ///
     /// # Example
     ///
-    /// Disable the amplifier.
-    ///
     /// ```no_run
-    /// use hackrfone::{HackRfOne, UnknownMode};
+    /// use hackrfone::{Capabilities, HackRfOne, UnknownMode};
     ///
     /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-    /// radio.set_amp_enable(false)?;
+    /// let capabilities: Capabilities = radio.capabilities();
+    /// if capabilities.clkout {
+    ///     radio.set_clkout_enable(true)?;
+    /// }
     /// # Ok::<(), hackrfone::Error>(())
     /// ```
-    pub fn set_amp_enable(&mut self, en: bool) -> Result<(), Error> {
-        self.write_control(Request::AmpEnable, en.into(), 0, &[])
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            sweep: self.supports_api_version(API_SWEEP),
+            operacake: self.supports_api_version(API_OPERACAKE),
+            clkout: self.supports_api_version(API_CLKOUT),
+            ui: self.supports_api_version(API_UI),
+            hw_sync: self.supports_api_version(API_HW_SYNC),
+        }
     }
 
-    /// Set the baseband filter bandwidth.
+    /// Set the timeout for USB transfers.
     ///
-    /// This is automatically set when the sample rate is changed with
-    /// [`set_sample_rate`].
+    /// A duration shorter than 1 ms is rejected with [`Error::Argument`];
+    /// the timeout is passed to libusb in whole milliseconds and libusb
+    /// treats a zero timeout as "wait forever", which hangs the program
+    /// instead of failing fast.
+    /// Use [`set_timeout_infinite`] if that is what you want.
     ///
     /// # Example
     ///
-    /// Set the filter bandwidth to 70% of the sample rate.
+    /// Set a 100ms timeout.
     ///
     /// ```no_run
     /// use hackrfone::{HackRfOne, UnknownMode};
-    ///
-    /// const SAMPLE_HZ: u32 = 20_000_000;
-    /// const SAMPLE_DIV: u32 = 2;
-    /// const FILTER_BW: u32 = (0.7 * (SAMPLE_HZ as f32) / (SAMPLE_DIV as f32)) as u32;
+    /// use std::time::Duration;
     ///
     /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-    /// radio.set_sample_rate(SAMPLE_HZ, SAMPLE_DIV)?;
-    /// radio.set_baseband_filter_bandwidth(FILTER_BW)?;
+    /// radio.set_timeout(Duration::from_millis(100))?;
     /// # Ok::<(), hackrfone::Error>(())
     /// ```
     ///
-    /// [`set_sample_rate`]: crate::HackRfOne::set_sample_rate
-    pub fn set_baseband_filter_bandwidth(&mut self, hz: u32) -> Result<(), Error> {
-        self.write_control(
-            Request::BasebandFilterBandwidthSet,
-            (hz & 0xFFFF) as u16,
-            (hz >> 16) as u16,
-            &[],
-        )
+    /// [`set_timeout_infinite`]: crate::HackRfOne::set_timeout_infinite
+    pub fn set_timeout(&mut self, duration: Duration) -> Result<(), Error> {
+        if valid_timeout(duration) {
+            self.to = duration;
+            self.saved_to = None;
+            Ok(())
+        } else {
+            Err(Error::Argument)
+        }
     }
 
-    /// Set the sample rate.
-    ///
-    /// For anti-aliasing, the baseband filter bandwidth is automatically set to
-    /// the widest available setting that is no more than 75% of the sample rate.
-    /// This happens every time the sample rate is set.
-    /// If you want to override the baseband filter selection, you must do so
-    /// after setting the sample rate.
+    /// Disable the timeout for USB transfers.
     ///
-    /// Limits are 8MHz - 20MHz.
-    /// Preferred rates are 8, 10, 12.5, 16, 20MHz due to less jitter.
+    /// Every USB transfer will block until it completes, potentially forever.
     ///
     /// # Example
     ///
-    /// Set the sample rate to 10 MHz.
-    ///
     /// ```no_run
     /// use hackrfone::{HackRfOne, UnknownMode};
     ///
     /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-    /// radio.set_sample_rate(20_000_000, 2)?;
-    /// # Ok::<(), hackrfone::Error>(())
+    /// radio.set_timeout_infinite();
     /// ```
-    pub fn set_sample_rate(&mut self, hz: u32, div: u32) -> Result<(), Error> {
-        let hz: u32 = hz.to_le();
-        let div: u32 = div.to_le();
-        let buf: [u8; 8] = [
-            (hz & 0xFF) as u8,
-            ((hz >> 8) & 0xFF) as u8,
-            ((hz >> 16) & 0xFF) as u8,
-            ((hz >> 24) & 0xFF) as u8,
-            (div & 0xFF) as u8,
-            ((div >> 8) & 0xFF) as u8,
-            ((div >> 16) & 0xFF) as u8,
-            ((div >> 24) & 0xFF) as u8,
-        ];
-        self.write_control(Request::SampleRateSet, 0, 0, &buf)?;
-        self.set_baseband_filter_bandwidth((0.75 * (hz as f32) / (div as f32)) as u32)
+    pub fn set_timeout_infinite(&mut self) {
+        self.to = Duration::ZERO;
+        self.saved_to = None;
     }
 
-    /// Set the LNA (low noise amplifier) gain.
+    /// Set the number of transfers an [`RxStream`] queues.
     ///
-    /// Range 0 to 40dB in 8dB steps.
+    /// This is the depth of the RX pipeline.
+    /// More transfers tolerate longer stalls in the consuming thread at the
+    /// cost of memory and latency; the memory used is the transfer count
+    /// multiplied by the [transfer size].
     ///
-    /// This is also known as the IF gain.
+    /// The default is [`TRANSFER_COUNT_DEFAULT`].
     ///
-    /// # Example
+    /// # Errors
     ///
-    /// Set the LNA gain to 16 dB (generally a reasonable gain to start with).
+    /// Returns [`Error::Argument`] if `n` is zero.
+    ///
+    /// # Example
     ///
     /// ```no_run
     /// use hackrfone::{HackRfOne, UnknownMode};
     ///
     /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-    /// radio.set_lna_gain(16)?;
+    /// radio.set_transfer_count(16)?;
     /// # Ok::<(), hackrfone::Error>(())
     /// ```
-    pub fn set_lna_gain(&mut self, gain: u16) -> Result<(), Error> {
-        if gain > 40 {
+    ///
+    /// [transfer size]: crate::HackRfOne::set_transfer_size
+    pub fn set_transfer_count(&mut self, n: usize) -> Result<(), Error> {
+        if n == 0 {
             Err(Error::Argument)
         } else {
-            let buf: [u8; 1] = self.read_control(Request::SetLnaGain, 0, gain & !0x07)?;
-            if buf[0] == 0 {
-                Err(Error::Argument)
-            } else {
-                Ok(())
-            }
+            self.transfer_count = n;
+            Ok(())
         }
     }
 
-    /// Set the VGA (variable gain amplifier) gain.
+    /// Get the number of transfers an [`RxStream`] queues.
+    pub fn transfer_count(&self) -> usize {
+        self.transfer_count
+    }
+
+    /// Set the size of a single [`RxStream`] transfer in bytes.
     ///
-    /// Range 0 to 62dB in 2dB steps.
+    /// Smaller transfers deliver samples sooner, reducing latency, but the
+    /// per-transfer overhead limits the sustained throughput.
+    /// Larger transfers are more efficient for high sample rates.
     ///
-    /// This is also known as the baseband (BB) gain.
+    /// The default is [`TRANSFER_SIZE_DEFAULT`].
     ///
-    /// # Example
+    /// # Errors
     ///
-    /// Set the VGA gain to 16 dB (generally a reasonable gain to start with).
+    /// Returns [`Error::Argument`] if `bytes` is zero or not a multiple of
+    /// [`USB_PACKET_SIZE`].
     ///
+    /// # Example
     ///
     /// ```no_run
     /// use hackrfone::{HackRfOne, UnknownMode};
     ///
     /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-    /// radio.set_vga_gain(16)?;
+    /// radio.set_transfer_size(64 * 1024)?;
     /// # Ok::<(), hackrfone::Error>(())
     /// ```
-    pub fn set_vga_gain(&mut self, gain: u16) -> Result<(), Error> {
-        if gain > 62 {
+    pub fn set_transfer_size(&mut self, bytes: usize) -> Result<(), Error> {
+        if bytes == 0 || !bytes.is_multiple_of(USB_PACKET_SIZE) {
             Err(Error::Argument)
         } else {
-            let buf: [u8; 1] = self.read_control(Request::SetVgaGain, 0, gain & !0b1)?;
-            if buf[0] == 0 {
-                Err(Error::Argument)
-            } else {
-                Ok(())
-            }
+            self.transfer_size = bytes;
+            Ok(())
         }
     }
 
-    /// Set the transmit VGA gain.
+    /// Get the size of a single [`RxStream`] transfer in bytes.
+    pub fn transfer_size(&self) -> usize {
+        self.transfer_size
+    }
+
+    /// Optimize [`rx`] for latency instead of throughput.
     ///
-    /// Range 0 to 47dB in 1db steps.
-    pub fn set_txvga_gain(&mut self, gain: u16) -> Result<(), Error> {
-        if gain > 47 {
-            Err(Error::Argument)
+    /// When enabled [`rx`] uses transfers of [`RX_MTU_LOW_LATENCY`] bytes
+    /// and the USB timeout is set to 100 ms, a stalled transfer is reported
+    /// quickly instead of blocking an interactive application.
+    /// When disabled [`rx`] uses transfers of [`RX_MTU_DEFAULT`] bytes and
+    /// the timeout in effect before low latency mode was enabled is restored.
+    /// A timeout set with [`set_timeout`] while low latency mode is enabled
+    /// is kept.
+    ///
+    /// The latency of [`rx`] is dominated by the time the radio takes to
+    /// fill one transfer.
+    /// At 10 MS/s (20 MB/s) the computed fill time of a 128 KiB transfer is
+    /// 6.55 ms, and of a 4 KiB transfer 0.2 ms.
+    /// The cost is a much higher rate of transfers, the per-transfer
+    /// overhead makes it easy to drop samples at high sample rates;
+    /// use the default for recording.
+    ///
+    /// Low latency mode is disabled by default.
+    /// This does not affect [`RxStream`], which uses the [transfer size].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode, RX_MTU_LOW_LATENCY};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_low_latency(true);
+    /// assert_eq!(radio.rx_mtu(), RX_MTU_LOW_LATENCY);
+    /// ```
+    ///
+    /// [`rx`]: crate::HackRfOne::rx
+    /// [`set_timeout`]: crate::HackRfOne::set_timeout
+    /// [transfer size]: crate::HackRfOne::set_transfer_size
+    pub fn set_low_latency(&mut self, enable: bool) {
+        self.rx_mtu = if enable {
+            RX_MTU_LOW_LATENCY
         } else {
-            let buf: [u8; 1] = self.read_control(Request::SetTxvgaGain, 0, gain)?;
-            if buf[0] == 0 {
-                Err(Error::Argument)
-            } else {
-                Ok(())
-            }
-        }
+            RX_MTU_DEFAULT
+        };
+        low_latency_timeout(enable, &mut self.to, &mut self.saved_to);
     }
 
-    /// Antenna power port control.
+    /// Get the size of a single [`rx`] transfer in bytes.
     ///
-    /// The source docs are a little lacking in terms of explanations here.
-    pub fn set_antenna_enable(&mut self, value: u8) -> Result<(), Error> {
-        self.write_control(Request::AntennaEnable, value.into(), 0, &[])
+    /// [`rx`]: crate::HackRfOne::rx
+    pub fn rx_mtu(&self) -> usize {
+        self.rx_mtu
     }
 
-    /// CLKOUT enable.
+    /// Read the board ID.
     ///
-    /// The source docs are a little lacking in terms of explanations here.
-    pub fn set_clkout_enable(&mut self, en: bool) -> Result<(), Error> {
-        self.check_api_version(Version::from_bcd(0x0103))?;
-        self.write_control(Request::ClkoutEnable, en.into(), 0, &[])
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// assert_eq!(radio.board_id()?, 0x02);
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn board_id(&self) -> Result<u8, Error> {
+        let data: [u8; 1] = self.read_control(Request::BoardIdRead, 0, 0)?;
+        Ok(data[0])
     }
 
-    /// Reset the HackRF radio.
+    /// Measure the round-trip latency of a control transfer.
+    ///
+    /// This times a single board ID read.
+    /// It measures one control round-trip, useful for comparing hubs and
+    /// cables for retune latency, not bulk throughput.
     ///
     /// # Example
     ///
     /// ```no_run
     /// use hackrfone::{HackRfOne, UnknownMode};
+    /// use std::time::Duration;
     ///
     /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-    /// let mut radio: HackRfOne<UnknownMode> = radio.reset()?;
+    /// let latency: Duration = radio.ping()?;
+    /// println!("control latency: {latency:?}");
     /// # Ok::<(), hackrfone::Error>(())
     /// ```
-    pub fn reset(mut self) -> Result<HackRfOne<UnknownMode>, Error> {
-        self.check_api_version(Version::from_bcd(0x0102))?;
-        self.write_control(Request::Reset, 0, 0, &[])?;
-        Ok(HackRfOne {
-            dh: self.dh,
-            desc: self.desc,
-            mode: UnknownMode,
-            to: self.to,
-        })
+    pub fn ping(&self) -> Result<Duration, Error> {
+        let start: Instant = Instant::now();
+        self.board_id()?;
+        Ok(start.elapsed())
     }
 
-    fn set_transceiver_mode(&mut self, mode: TranscieverMode) -> Result<(), Error> {
-        self.write_control(Request::SetTransceiverMode, mode.into(), 0, &[])
+    /// Wait until the device responds to requests.
+    ///
+    /// Right after power up or a reset control requests can fail for a few
+    /// hundred milliseconds while the firmware initializes.
+    /// This reads the board ID every 50 ms until a read succeeds, use it
+    /// instead of a fixed sleep after opening the device.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last read if the device does not respond
+    /// within `timeout`.
+    /// Returns `Error::Usb(rusb::Error::NoDevice)` immediately if the device
+    /// has left the bus, re-open it with [`reset_and_reopen`] instead.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    /// use std::time::Duration;
+    ///
+    /// let radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.wait_ready(Duration::from_secs(1))?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`reset_and_reopen`]: crate::HackRfOne::reset_and_reopen
+    pub fn wait_ready(&self, timeout: Duration) -> Result<(), Error> {
+        const POLL: Duration = Duration::from_millis(50);
+
+        let start: Instant = Instant::now();
+        loop {
+            match self.board_id() {
+                Ok(_) => return Ok(()),
+                Err(e @ Error::Usb(rusb::Error::NoDevice)) => return Err(e),
+                Err(e) if start.elapsed() >= timeout => return Err(e),
+                Err(_) => thread::sleep(POLL),
+            }
+        }
     }
 
-    /// Change the radio mode to RX.
+    /// Read the board variant.
+    ///
+    /// The board ID is read on the first call, later calls return the
+    /// cached variant.
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use hackrfone::{HackRfOne, RxMode, UnknownMode};
+    /// use hackrfone::{HackRfOne, UnknownMode, Variant};
     ///
     /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-    /// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+    /// assert_eq!(radio.variant()?, Variant::HackRfOne);
     /// # Ok::<(), hackrfone::Error>(())
     /// ```
-    pub fn into_rx_mode(mut self) -> Result<HackRfOne<RxMode>, Error> {
-        self.set_transceiver_mode(TranscieverMode::Receive)?;
-        self.dh.claim_interface(0)?;
-        Ok(HackRfOne {
-            dh: self.dh,
-            desc: self.desc,
-            mode: RxMode,
-            to: self.to,
-        })
+    pub fn variant(&self) -> Result<Variant, Error> {
+        if let Some(variant) = self.cache.variant.get() {
+            return Ok(*variant);
+        }
+        let variant: Variant = self.board_id()?.into();
+        Ok(*self.cache.variant.get_or_init(|| variant))
     }
-}
 
-impl HackRfOne<RxMode> {
-    /// Receive data from the radio.
-    ///
-    /// This uses a bulk transfer to get one MTU (maximum transmission unit)
-    /// of data in a single shot.  The data format is pairs of signed 8-bit IQ.
-    /// Use the [`iq_to_cplx_i8`] or [`iq_to_cplx_f32`] helpers to convert the
-    /// data to a more manageable format.
+    /// Maximum supported sample rate in Hz for this board variant.
     ///
-    /// Unlike `libhackrf` this does not spawn a sampling thread.
+    /// The variant is read once, see [`variant`].
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use hackrfone::{HackRfOne, RxMode, UnknownMode};
+    /// use hackrfone::{HackRfOne, UnknownMode};
     ///
     /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-    /// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
-    /// let data: Vec<u8> = radio.rx()?;
-    /// radio.stop_rx()?;
+    /// assert_eq!(radio.max_sample_rate()?, 20_000_000);
     /// # Ok::<(), hackrfone::Error>(())
     /// ```
     ///
-    /// [`iq_to_cplx_i8`]: crate::iq_to_cplx_i8
-    /// [`iq_to_cplx_f32`]: crate::iq_to_cplx_f32
-    #[cfg_attr(not(feature = "num-complex"), allow(rustdoc::broken_intra_doc_links))]
-    pub fn rx(&mut self) -> Result<Vec<u8>, Error> {
-        const ENDPOINT: u8 = 0x81;
-        const MTU: usize = 128 * 1024;
-        let mut buf: Vec<u8> = vec![0; MTU];
-        let n: usize = self.dh.read_bulk(ENDPOINT, &mut buf, self.to)?;
-        buf.truncate(n);
-        Ok(buf)
+    /// [`variant`]: crate::HackRfOne::variant
+    pub fn max_sample_rate(&self) -> Result<u32, Error> {
+        Ok(self.variant()?.max_sample_rate())
     }
 
-    /// Stop receiving.
+    /// Negotiated USB speed of the device.
+    ///
+    /// The HackRF One requires a high speed (USB 2.0) link for all but the
+    /// lowest sample rates.
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use hackrfone::{HackRfOne, RxMode, UnknownMode};
+    /// use hackrfone::{rusb::Speed, HackRfOne, UnknownMode};
     ///
     /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-    /// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
-    /// let data: Vec<u8> = radio.rx()?;
-    /// radio.stop_rx()?;
+    /// assert_eq!(radio.usb_speed(), Speed::High);
     /// # Ok::<(), hackrfone::Error>(())
     /// ```
-    pub fn stop_rx(mut self) -> Result<HackRfOne<UnknownMode>, Error> {
-        self.dh.release_interface(0)?;
-        self.set_transceiver_mode(TranscieverMode::Off)?;
-        Ok(HackRfOne {
-            dh: self.dh,
-            desc: self.desc,
-            mode: UnknownMode,
-            to: self.to,
-        })
+    pub fn usb_speed(&self) -> rusb::Speed {
+        self.dev.dh.device().speed()
     }
-}
 
-// Helper for set_freq
-fn freq_params(hz: u64) -> [u8; 8] {
-    const MHZ: u64 = 1_000_000;
+    /// USB bandwidth in Mbit/s required to stream at the sample rate set
+    /// with [`set_sample_rate`].
+    ///
+    /// Each sample is two bytes, one byte each for I and Q.
+    /// Returns `None` if the sample rate has not been set through this
+    /// handle.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_sample_rate(20_000_000, 1)?;
+    /// assert_eq!(radio.required_usb_bandwidth_mbps(), Some(320.0));
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`set_sample_rate`]: crate::HackRfOne::set_sample_rate
+    pub fn required_usb_bandwidth_mbps(&self) -> Option<f64> {
+        self.cache
+            .sample_rate
+            .map(|(hz, div)| f64::from(hz) / f64::from(div) * 2.0 * 8.0 / 1e6)
+    }
 
-    let l_freq_mhz: u32 = u32::try_from(hz / MHZ).unwrap_or(u32::MAX).to_le();
-    let l_freq_hz: u32 = u32::try_from(hz - u64::from(l_freq_mhz) * MHZ)
-        .unwrap_or(u32::MAX)
-        .to_le();
+    fn check_usb_bandwidth(&self) -> Result<(), Error> {
+        check_bandwidth(self.required_usb_bandwidth_mbps(), self.usb_speed())
+    }
 
-    [
-        (l_freq_mhz & 0xFF) as u8,
-        ((l_freq_mhz >> 8) & 0xFF) as u8,
-        ((l_freq_mhz >> 16) & 0xFF) as u8,
-        ((l_freq_mhz >> 24) & 0xFF) as u8,
-        (l_freq_hz & 0xFF) as u8,
-        ((l_freq_hz >> 8) & 0xFF) as u8,
-        ((l_freq_hz >> 16) & 0xFF) as u8,
-        ((l_freq_hz >> 24) & 0xFF) as u8,
-    ]
-}
+    /// Read the firmware version.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// assert_eq!(radio.version()?, "2021.03.1");
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn version(&self) -> Result<String, Error> {
+        let mut buf: [u8; 16] = [0; 16];
+        let result: Result<usize, rusb::Error> = self.dev.dh.read_control(
+            request_type(Direction::In, RequestType::Vendor, Recipient::Device),
+            Request::VersionStringRead.into(),
+            0,
+            0,
+            &mut buf,
+            self.to,
+        );
+        trace::control(
+            Direction::In,
+            Request::VersionStringRead,
+            0,
+            0,
+            buf.len(),
+            &result,
+        );
+        let n: usize = result?;
+        Ok(String::from_utf8_lossy(&buf[0..n]).into())
+    }
 
-#[cfg(test)]
-mod freq_params {
-    use super::freq_params;
+    /// Read the firmware build information.
+    ///
+    /// This is parsed from the [version] string, the firmware does not
+    /// report any other build metadata such as the build date.
+    /// Release builds report the release version, development builds
+    /// report the git commit hash.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{FirmwareBuildInfo, HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let info: FirmwareBuildInfo = radio.firmware_build_info()?;
+    /// assert_eq!(info.release.as_deref(), Some("2021.03.1"));
+    /// assert_eq!(info.git_hash, None);
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [version]: crate::HackRfOne::version
+    pub fn firmware_build_info(&self) -> Result<FirmwareBuildInfo, Error> {
+        Ok(FirmwareBuildInfo::parse(&self.version()?))
+    }
 
-    #[test]
-    fn nominal() {
-        assert_eq!(freq_params(915_000_000), [0x93, 0x03, 0, 0, 0, 0, 0, 0]);
-        assert_eq!(freq_params(915_000_001), [0x93, 0x03, 0, 0, 1, 0, 0, 0]);
-        assert_eq!(
-            freq_params(123456789),
-            [0x7B, 0, 0, 0, 0x55, 0xF8, 0x06, 0x00]
-        );
+    /// Read the serial number.
+    ///
+    /// The HackRF C API calls the equivalent of this function
+    /// `hackrf_board_partid_serialno_read`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let serial: [u32; 4] = radio.serial_number()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn serial_number(&self) -> Result<[u32; 4], Error> {
+        // 2 words of part ID followed by 4 words of serial number
+        let data: [u8; 24] = self.read_control(Request::BoardPartidSerialnoRead, 0, 0)?;
+        let mut serial: [u32; 4] = [0; 4];
+        serial
+            .iter_mut()
+            .zip(data[8..].chunks_exact(4))
+            .for_each(|(word, bytes)| {
+                *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+            });
+        Ok(serial)
     }
 
-    #[test]
-    fn min() {
-        assert_eq!(freq_params(0), [0; 8]);
+    /// Read the first `len` bytes of the SPI flash containing the firmware.
+    ///
+    /// The flash is read one page at a time, reading the full flash takes
+    /// several seconds.
+    /// Use [`read_firmware_with_progress`] to report progress.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if `len` exceeds [`SPIFLASH_SIZE`].
+    ///
+    /// # Example
+    ///
+    /// Back up the firmware.
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode, SPIFLASH_SIZE};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let firmware: Vec<u8> = radio.read_firmware(SPIFLASH_SIZE as u32)?;
+    /// std::fs::write("hackrf_one_usb.bin", firmware)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`read_firmware_with_progress`]: crate::HackRfOne::read_firmware_with_progress
+    pub fn read_firmware(&self, len: u32) -> Result<Vec<u8>, Error> {
+        self.read_firmware_with_progress(len, |_, _| {})
     }
 
-    #[test]
-    fn max() {
-        assert_eq!(freq_params(u64::MAX), [0xFF; 8]);
+    /// Read the first `len` bytes of the SPI flash, reporting progress.
+    ///
+    /// This is the same as [`read_firmware`], and calls
+    /// `progress(done, total)` with the number of bytes read so far after
+    /// each page.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode, SPIFLASH_SIZE};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let firmware: Vec<u8> =
+    ///     radio.read_firmware_with_progress(SPIFLASH_SIZE as u32, |done, total| {
+    ///         eprint!("\r{}%", done * 100 / total)
+    ///     })?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`read_firmware`]: crate::HackRfOne::read_firmware
+    pub fn read_firmware_with_progress(
+        &self,
+        len: u32,
+        mut progress: impl FnMut(u32, u32),
+    ) -> Result<Vec<u8>, Error> {
+        if len as usize > SPIFLASH_SIZE {
+            return Err(Error::Argument);
+        }
+        let mut buf: Vec<u8> = vec![0; len as usize];
+        let mut addr: u32 = 0;
+        for page in buf.chunks_mut(SPIFLASH_PAGE_SIZE) {
+            let (value, index): (u16, u16) = split_flash_address(addr)?;
+            self.read_control_into(Request::SpiflashRead, value, index, page)?;
+            addr += page.len() as u32;
+            progress(addr, len);
+        }
+        Ok(buf)
     }
-}
 
-/// Convert an IQ sample pair to a complex number.
-///
-/// # Example
-///
-/// Post-processing sample data.
-///
-/// ```no_run
-/// use hackrfone::{iq_to_cplx_i8, HackRfOne, RxMode, UnknownMode};
+    /// Set the center frequency.
+    ///
+    /// If automatic amplifier control is enabled with [`set_amp_auto`] the
+    /// amplifier is switched according to the new frequency.
+    ///
+    /// If a frequency correction is set with [`set_freq_ppm_correction`] the
+    /// radio is tuned to the corrected frequency.
+    ///
+    /// # Example
+    ///
+    /// Set the frequency to 915MHz.
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_freq(915_000_000)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`set_amp_auto`]: crate::HackRfOne::set_amp_auto
+    /// [`set_freq_ppm_correction`]: crate::HackRfOne::set_freq_ppm_correction
+    pub fn set_freq(&mut self, hz: u64) -> Result<(), Error> {
+        let tuned_hz: u64 = match self.cache.freq_correction_ppm {
+            Some(ppm) => corrected_freq(hz, ppm),
+            None => hz,
+        };
+        let buf: [u8; 8] = freq_params(tuned_hz);
+        self.write_control(Request::SetFreq, 0, 0, &buf)?;
+        self.cache.freq = Some(hz);
+        self.cache.freq_params = Some(buf);
+        self.amp_auto(hz)
+    }
+
+    /// Switch the amplifier for `hz` if automatic amplifier control is
+    /// enabled.
+    fn amp_auto(&mut self, hz: u64) -> Result<(), Error> {
+        match self.cache.amp_auto_above {
+            Some(above_hz) => self.write_amp_enable(hz > above_hz),
+            None => Ok(()),
+        }
+    }
+
+    /// Set a frequency correction for the crystal error in parts per million.
+    ///
+    /// Every following call to [`set_freq`] tunes to
+    /// `hz * (1 + ppm / 1e6)` instead of `hz`, this is the frequency
+    /// correction setting of applications such as GQRX and SDR#.
+    /// Measure `ppm` against a known reference, a typical HackRF One is
+    /// within ±20 ppm.
+    ///
+    /// This adjusts the frequency the radio is tuned to, not the reference
+    /// clock; the sample rate still has the crystal error.
+    /// The frequency is not retuned, call [`set_freq`] to apply the
+    /// correction.
+    /// [`set_freq_explicit`] and [`set_rf_path`] are not corrected.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if `ppm` is not finite.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_freq_ppm_correction(-2.5)?;
+    /// radio.set_freq(915_000_000)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`set_freq`]: crate::HackRfOne::set_freq
+    /// [`set_freq_explicit`]: crate::HackRfOne::set_freq_explicit
+    /// [`set_rf_path`]: crate::HackRfOne::set_rf_path
+    pub fn set_freq_ppm_correction(&mut self, ppm: f32) -> Result<(), Error> {
+        if ppm.is_finite() {
+            self.cache.freq_correction_ppm = Some(ppm).filter(|&ppm| ppm != 0.0);
+            Ok(())
+        } else {
+            Err(Error::Argument)
+        }
+    }
+
+    /// Get the frequency correction in parts per million.
+    ///
+    /// This is zero unless set with [`set_freq_ppm_correction`].
+    ///
+    /// [`set_freq_ppm_correction`]: crate::HackRfOne::set_freq_ppm_correction
+    pub fn freq_ppm_correction(&self) -> f32 {
+        self.cache.freq_correction_ppm.unwrap_or(0.0)
+    }
+
+    /// Get the payload last sent to the device to set the center frequency.
+    ///
+    /// This is the 8 byte `SetFreq` request data, the frequency in MHz
+    /// followed by the remaining frequency in Hz, both as little endian
+    /// `u32`.
+    /// Returns `None` if the frequency has not been set through this handle,
+    /// or was last set with [`set_freq_explicit`], which sends a different
+    /// request.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_freq(915_000_001)?;
+    /// assert_eq!(
+    ///     radio.last_freq_params(),
+    ///     Some([0x93, 0x03, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00])
+    /// );
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`set_freq_explicit`]: crate::HackRfOne::set_freq_explicit
+    pub fn last_freq_params(&self) -> Option<[u8; 8]> {
+        self.cache.freq_params
+    }
+
+    /// Set the IF and LO frequencies and the RF path filter explicitly.
+    ///
+    /// This bypasses the automatic frequency plan of [`set_freq`], the
+    /// resulting RF frequency depends on `path`, see [`RfPath`].
+    /// The LO frequency is ignored for [`RfPath::Bypass`].
+    ///
+    /// Like [`set_freq`], the amplifier is switched according to the
+    /// resulting RF frequency if automatic amplifier control is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if `if_hz` is outside of 2.15 GHz to
+    /// 2.75 GHz, or `lo_hz` is outside of 84.375 MHz to 5.4 GHz.
+    ///
+    /// # Example
+    ///
+    /// Receive at 100 MHz with a 2.6 GHz IF.
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, RfPath, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_freq_explicit(2_600_000_000, 2_700_000_000, RfPath::LowPass)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`set_freq`]: crate::HackRfOne::set_freq
+    pub fn set_freq_explicit(&mut self, if_hz: u64, lo_hz: u64, path: RfPath) -> Result<(), Error> {
+        let rf_hz: u64 = explicit_rf_freq(if_hz, lo_hz, path).ok_or(Error::Argument)?;
+        let buf: [u8; 17] = freq_explicit_params(if_hz, lo_hz, path);
+        self.write_control(Request::SetFreqExplicit, 0, 0, &buf)?;
+        self.cache.freq = Some(rf_hz);
+        self.cache.freq_params = None;
+        self.amp_auto(rf_hz)
+    }
+
+    /// Force the RF path filter, keeping the current center frequency.
+    ///
+    /// By default [`set_freq`] selects the path automatically; low pass
+    /// below 2.17 GHz, bypass from 2.17 GHz to 2.74 GHz, and high pass above.
+    /// This retunes with [`set_freq_explicit`] to keep the center frequency
+    /// set through this handle while using `path`, for experiments such as
+    /// harmonic mixing or to move a filter edge away from a signal.
+    /// Call [`set_freq`] to return to automatic path selection.
+    ///
+    /// Forcing a path the frequency plan would not choose can degrade
+    /// performance, with lower sensitivity and more images and spurs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] if the center frequency has not been set
+    /// through this handle, and [`Error::Argument`] if the center frequency
+    /// cannot be reached through `path`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, RfPath, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_freq(2_450_000_000)?;
+    /// radio.set_rf_path(RfPath::HighPass)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`set_freq`]: crate::HackRfOne::set_freq
+    /// [`set_freq_explicit`]: crate::HackRfOne::set_freq_explicit
+    pub fn set_rf_path(&mut self, path: RfPath) -> Result<(), Error> {
+        let rf_hz: u64 = self.cache.freq.ok_or(Error::Config)?;
+        let (if_hz, lo_hz): (u64, u64) = forced_path_plan(rf_hz, path);
+        self.set_freq_explicit(if_hz, lo_hz, path)
+    }
+
+    /// Enable the RX/TX RF amplifier.
+    ///
+    /// In GNU radio this is used as the RF gain, where a value of 0 dB is off,
+    /// and a value of 14 dB is on.
+    ///
+    /// # Example
+    ///
+    /// Disable the amplifier.
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_amp_enable(false)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn set_amp_enable(&mut self, en: bool) -> Result<(), Error> {
+        self.cache.amp_auto_above = None;
+        self.write_amp_enable(en)
+    }
+
+    fn write_amp_enable(&mut self, en: bool) -> Result<(), Error> {
+        self.write_control(Request::AmpEnable, en.into(), 0, &[])?;
+        self.cache.amp_enable = Some(en);
+        Ok(())
+    }
+
+    /// Enable the RF amplifier only above a frequency.
+    ///
+    /// For wideband scanning the amplifier helps with weak signals at high
+    /// frequencies, but it is easily overloaded by strong signals at low
+    /// frequencies.
+    /// With a threshold set, every call to [`set_freq`] enables the amplifier
+    /// if the frequency is above `above_hz`, and disables it otherwise.
+    /// If a frequency has already been set the amplifier is updated
+    /// immediately.
+    ///
+    /// Pass `None` to disable automatic control, this leaves the amplifier
+    /// in its current state.
+    /// Calling [`set_amp_enable`] also disables automatic control.
+    ///
+    /// # Example
+    ///
+    /// Enable the amplifier above 1 GHz.
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_amp_auto(Some(1_000_000_000))?;
+    /// radio.set_freq(433_000_000)?; // amplifier off
+    /// radio.set_freq(2_400_000_000)?; // amplifier on
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`set_freq`]: crate::HackRfOne::set_freq
+    /// [`set_amp_enable`]: crate::HackRfOne::set_amp_enable
+    pub fn set_amp_auto(&mut self, above_hz: Option<u64>) -> Result<(), Error> {
+        self.cache.amp_auto_above = above_hz;
+        match (above_hz, self.cache.freq) {
+            (Some(above_hz), Some(freq)) => self.write_amp_enable(freq > above_hz),
+            _ => Ok(()),
+        }
+    }
+
+    /// Set the baseband filter bandwidth.
+    ///
+    /// The firmware rounds the bandwidth up to the next entry of
+    /// [`BASEBAND_FILTER_BW`], the applied bandwidth is returned.
+    ///
+    /// This is automatically set when the sample rate is changed with
+    /// [`set_sample_rate`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if `hz` is outside of the range supported
+    /// by the hardware, 1.75 MHz to 28 MHz.
+    ///
+    /// # Example
+    ///
+    /// Set the filter bandwidth to 70% of the sample rate.
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// const SAMPLE_HZ: u32 = 20_000_000;
+    /// const SAMPLE_DIV: u32 = 2;
+    /// const FILTER_BW: u32 = (0.7 * (SAMPLE_HZ as f32) / (SAMPLE_DIV as f32)) as u32;
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_sample_rate(SAMPLE_HZ, SAMPLE_DIV)?;
+    /// assert_eq!(radio.set_baseband_filter_bandwidth(FILTER_BW)?, 7_000_000);
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`set_sample_rate`]: crate::HackRfOne::set_sample_rate
+    pub fn set_baseband_filter_bandwidth(&mut self, hz: u32) -> Result<u32, Error> {
+        if hz < BASEBAND_FILTER_BW[0] {
+            return Err(Error::Argument);
+        }
+        let hz: u32 = baseband_filter_bw_round_up(hz).ok_or(Error::Argument)?;
+        self.write_control(
+            Request::BasebandFilterBandwidthSet,
+            (hz & 0xFFFF) as u16,
+            (hz >> 16) as u16,
+            &[],
+        )?;
+        self.cache.baseband_filter_bw = Some(hz);
+        Ok(hz)
+    }
+
+    /// Set the sample rate.
+    ///
+    /// For anti-aliasing, the baseband filter bandwidth is automatically set to
+    /// the widest available setting that is no more than 75% of the sample rate,
+    /// or the narrowest setting for sample rates below 2.33 MHz.
+    /// This happens every time the sample rate is set.
+    /// If you want to override the baseband filter selection, you must do so
+    /// after setting the sample rate.
+    ///
+    /// The sample rate is `hz / div`.
+    /// The upper limit depends on the board variant, see [`max_sample_rate`].
+    /// Preferred rates are 8, 10, 12.5, 16, 20MHz due to less jitter.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if `div` is zero, the sample rate is zero,
+    /// or the sample rate exceeds the maximum for the board variant.
+    ///
+    /// # Example
+    ///
+    /// Set the sample rate to 10 MHz.
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_sample_rate(20_000_000, 2)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`max_sample_rate`]: crate::HackRfOne::max_sample_rate
+    pub fn set_sample_rate(&mut self, hz: u32, div: u32) -> Result<(), Error> {
+        if div == 0 || !valid_sample_rate(hz / div, self.max_sample_rate()?) {
+            return Err(Error::Argument);
+        }
+        let hz: u32 = hz.to_le();
+        let div: u32 = div.to_le();
+        let buf: [u8; 8] = [
+            (hz & 0xFF) as u8,
+            ((hz >> 8) & 0xFF) as u8,
+            ((hz >> 16) & 0xFF) as u8,
+            ((hz >> 24) & 0xFF) as u8,
+            (div & 0xFF) as u8,
+            ((div >> 8) & 0xFF) as u8,
+            ((div >> 16) & 0xFF) as u8,
+            ((div >> 24) & 0xFF) as u8,
+        ];
+        self.write_control(Request::SampleRateSet, 0, 0, &buf)?;
+        self.cache.sample_rate = Some((hz, div));
+        let bw: u32 = baseband_filter_bw_round_down((0.75 * (hz as f32) / (div as f32)) as u32);
+        self.set_baseband_filter_bandwidth(bw).map(|_| ())
+    }
+
+    /// Get the sample rate set through this handle as `(hz, div)`.
+    ///
+    /// Returns `None` if the sample rate has not been set through this
+    /// handle.
+    ///
+    /// The firmware has no request to read back the sample rate, this is
+    /// the value cached on the host.
+    /// A device that was configured by another process, or before a
+    /// reconnect, is running at an unknown rate; set the sample rate
+    /// explicitly after opening the device if it matters.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// assert_eq!(radio.sample_rate(), None);
+    /// radio.set_sample_rate(20_000_000, 2)?;
+    /// assert_eq!(radio.sample_rate(), Some((20_000_000, 2)));
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn sample_rate(&self) -> Option<(u32, u32)> {
+        self.cache.sample_rate
+    }
+
+    /// Set up the radio to receive a single channel.
+    ///
+    /// This picks a channel rate of twice `channel_bw_hz`, and a hardware
+    /// sample rate that is a whole multiple of the channel rate, at least
+    /// the 2 MHz minimum sample rate of the HackRF One.
+    /// The baseband filter is set to the narrowest bandwidth that passes
+    /// the channel, then the radio is tuned to `center_hz`.
+    ///
+    /// The returned [`ChannelPlan`] has the decimation factor to apply in
+    /// software, after low-pass filtering, to get from the hardware sample
+    /// rate to the channel rate.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if the channel rate exceeds the maximum
+    /// sample rate or `channel_bw_hz` is zero.
+    ///
+    /// # Example
+    ///
+    /// A 200 kHz FM broadcast channel.
+    ///
+    /// ```no_run
+    /// use hackrfone::{ChannelPlan, HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let plan: ChannelPlan = radio.configure_channel(100_100_000, 200_000)?;
+    /// assert_eq!(plan.sample_rate, 2_000_000);
+    /// assert_eq!(plan.decimation, 5);
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn configure_channel(
+        &mut self,
+        center_hz: u64,
+        channel_bw_hz: u32,
+    ) -> Result<ChannelPlan, Error> {
+        let plan: ChannelPlan =
+            channel_plan(channel_bw_hz, self.max_sample_rate()?).ok_or(Error::Argument)?;
+        self.set_sample_rate(plan.sample_rate, 1)?;
+        self.set_baseband_filter_bandwidth(plan.baseband_filter_bw)?;
+        self.set_freq(center_hz)?;
+        Ok(plan)
+    }
+
+    /// Check the sample rate and baseband filter for consistency.
+    ///
+    /// A baseband filter wider than the sample rate lets signals outside the
+    /// sampled bandwidth alias into the spectrum.
+    /// This can happen when the filter is set explicitly and the sample rate
+    /// is lowered afterwards without setting the filter again.
+    ///
+    /// The check uses the settings applied through this handle, it passes if
+    /// the sample rate or filter have not been set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] if the filter bandwidth exceeds the sample
+    /// rate.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_sample_rate(20_000_000, 2)?;
+    /// radio.set_baseband_filter_bandwidth(15_000_000)?;
+    /// assert!(radio.check_rf_config().is_err());
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn check_rf_config(&self) -> Result<(), Error> {
+        match (self.cache.sample_rate, self.cache.baseband_filter_bw) {
+            (Some((hz, div)), Some(bw)) if bw > hz / div => Err(Error::Config),
+            _ => Ok(()),
+        }
+    }
+
+    /// Set the LNA (low noise amplifier) gain.
+    ///
+    /// Range 0 to 40dB in 8dB steps.
+    /// The gain is rounded down to the nearest step, the applied gain is
+    /// returned.
+    ///
+    /// This is also known as the IF gain.
+    ///
+    /// # Example
+    ///
+    /// Set the LNA gain to 16 dB (generally a reasonable gain to start with).
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_lna_gain(16)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn set_lna_gain(&mut self, gain: u16) -> Result<u16, Error> {
+        if gain > 40 {
+            Err(Error::Argument)
+        } else {
+            let gain: u16 = gain & !0x07;
+            let buf: [u8; 1] = self.read_control(Request::SetLnaGain, 0, gain)?;
+            if buf[0] == 0 {
+                Err(Error::Argument)
+            } else {
+                self.cache.lna_gain = Some(gain);
+                Ok(gain)
+            }
+        }
+    }
+
+    /// Set the VGA (variable gain amplifier) gain.
+    ///
+    /// Range 0 to 62dB in 2dB steps.
+    /// The gain is rounded down to the nearest step, the applied gain is
+    /// returned.
+    ///
+    /// This is also known as the baseband (BB) gain.
+    ///
+    /// # Example
+    ///
+    /// Set the VGA gain to 16 dB (generally a reasonable gain to start with).
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_vga_gain(16)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn set_vga_gain(&mut self, gain: u16) -> Result<u16, Error> {
+        if gain > 62 {
+            Err(Error::Argument)
+        } else {
+            let gain: u16 = gain & !0b1;
+            let buf: [u8; 1] = self.read_control(Request::SetVgaGain, 0, gain)?;
+            if buf[0] == 0 {
+                Err(Error::Argument)
+            } else {
+                self.cache.vga_gain = Some(gain);
+                Ok(gain)
+            }
+        }
+    }
+
+    /// Get the gains last applied through this handle.
+    ///
+    /// Returns the LNA, VGA, and transmit VGA gains, after rounding to the
+    /// hardware steps.
+    /// A gain that has not been set through this handle is `None`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_lna_gain(20)?;
+    /// radio.set_vga_gain(33)?;
+    /// assert_eq!(radio.last_applied_gains(), (Some(16), Some(32), None));
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn last_applied_gains(&self) -> (Option<u16>, Option<u16>, Option<u16>) {
+        (
+            self.cache.lna_gain,
+            self.cache.vga_gain,
+            self.cache.txvga_gain,
+        )
+    }
+
+    /// Get the RX settings applied through this handle.
+    ///
+    /// Settings that have not been set through this handle are `None`.
+    ///
+    /// # Example
+    ///
+    /// Match the settings of radio B to radio A.
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, RxConfig, UnknownMode};
+    ///
+    /// let mut a: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// # let mut b: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// a.set_freq(915_000_000)?;
+    /// a.set_lna_gain(16)?;
+    /// b.configure_rx(&a.current_config())?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn current_config(&self) -> RxConfig {
+        RxConfig {
+            freq: self.cache.freq,
+            sample_rate: self.cache.sample_rate,
+            baseband_filter_bw: self.cache.baseband_filter_bw,
+            lna_gain: self.cache.lna_gain,
+            vga_gain: self.cache.vga_gain,
+            amp_enable: self.cache.amp_enable,
+        }
+    }
+
+    /// Apply RX settings.
+    ///
+    /// Settings that are `None` are left unchanged.
+    /// The sample rate is applied before the baseband filter bandwidth, so
+    /// an explicit bandwidth overrides the automatic selection of
+    /// [`set_sample_rate`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, RxConfig, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.configure_rx(&RxConfig {
+    ///     freq: Some(915_000_000),
+    ///     sample_rate: Some((20_000_000, 2)),
+    ///     ..Default::default()
+    /// })?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`set_sample_rate`]: crate::HackRfOne::set_sample_rate
+    pub fn configure_rx(&mut self, config: &RxConfig) -> Result<(), Error> {
+        if let Some((hz, div)) = config.sample_rate {
+            self.set_sample_rate(hz, div)?;
+        }
+        if let Some(bw) = config.baseband_filter_bw {
+            self.set_baseband_filter_bandwidth(bw)?;
+        }
+        if let Some(hz) = config.freq {
+            self.set_freq(hz)?;
+        }
+        if let Some(gain) = config.lna_gain {
+            self.set_lna_gain(gain)?;
+        }
+        if let Some(gain) = config.vga_gain {
+            self.set_vga_gain(gain)?;
+        }
+        if let Some(en) = config.amp_enable {
+            self.set_amp_enable(en)?;
+        }
+        Ok(())
+    }
+
+    /// Set the transmit VGA gain.
+    ///
+    /// Range 0 to 47dB in 1db steps.
+    /// The applied gain is returned.
+    pub fn set_txvga_gain(&mut self, gain: u16) -> Result<u16, Error> {
+        if gain > 47 {
+            Err(Error::Argument)
+        } else {
+            let buf: [u8; 1] = self.read_control(Request::SetTxvgaGain, 0, gain)?;
+            if buf[0] == 0 {
+                Err(Error::Argument)
+            } else {
+                self.cache.txvga_gain = Some(gain);
+                Ok(gain)
+            }
+        }
+    }
+
+    /// Antenna power port control.
+    ///
+    /// The source docs are a little lacking in terms of explanations here.
+    ///
+    /// The firmware does not report the state of the antenna port power,
+    /// there is no way to detect an overcurrent or short circuit on the
+    /// port.
+    /// Hardware revisions with a current limited port switch it off on a
+    /// fault without notifying the host, check external equipment powered
+    /// from the port by other means.
+    pub fn set_antenna_enable(&mut self, value: u8) -> Result<(), Error> {
+        self.write_control(Request::AntennaEnable, value.into(), 0, &[])
+    }
+
+    /// CLKOUT enable.
+    ///
+    /// The source docs are a little lacking in terms of explanations here.
+    pub fn set_clkout_enable(&mut self, en: bool) -> Result<(), Error> {
+        self.check_api_version(API_CLKOUT)?;
+        self.write_control(Request::ClkoutEnable, en.into(), 0, &[])
+    }
+
+    /// Get the addresses of connected Opera Cake antenna switch boards.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let boards: Vec<u8> = radio.operacake_boards()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn operacake_boards(&self) -> Result<Vec<u8>, Error> {
+        self.check_api_version(API_OPERACAKE)?;
+        let buf: [u8; OPERACAKE_MAX_BOARDS] =
+            self.read_control(Request::OperacakeGetBoards, 0, 0)?;
+        Ok(operacake_addresses(&buf))
+    }
+
+    /// Switch the common ports of an Opera Cake.
+    ///
+    /// A0 is switched to `port_a` and B0 to `port_b`, one of the ports must
+    /// be on the A side and the other on the B side.
+    ///
+    /// The HackRF One is assumed to be connected to A0, `port_a` is
+    /// remembered as the active port, see [`operacake_port`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if both ports are on the same side.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, OperacakePort, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_operacake_ports(0, OperacakePort::A2, OperacakePort::B1)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`operacake_port`]: crate::HackRfOne::operacake_port
+    pub fn set_operacake_ports(
+        &mut self,
+        address: u8,
+        port_a: OperacakePort,
+        port_b: OperacakePort,
+    ) -> Result<(), Error> {
+        self.check_api_version(API_OPERACAKE)?;
+        let index: u16 = operacake_ports_index(port_a, port_b).ok_or(Error::Argument)?;
+        self.write_control(Request::OperacakeSetPorts, address.into(), index, &[])?;
+        self.cache.operacake_port = Some(port_a);
+        Ok(())
+    }
+
+    /// Get the Opera Cake port switched to the HackRF One.
+    ///
+    /// This is the last `port_a` set with [`set_operacake_ports`], `None`
+    /// if the ports have not been set through this handle.
+    ///
+    /// [`set_operacake_ports`]: crate::HackRfOne::set_operacake_ports
+    pub fn operacake_port(&self) -> Option<OperacakePort> {
+        self.cache.operacake_port
+    }
+
+    /// Set the power offsets of the Opera Cake ports used by
+    /// [`power_dbfs`].
+    ///
+    /// [`power_dbfs`]: crate::HackRfOne::power_dbfs
+    pub fn set_port_calibration(&mut self, cal: PortCalibration) {
+        self.cache.port_calibration = cal;
+    }
+
+    /// Get the power offsets of the Opera Cake ports.
+    pub fn port_calibration(&self) -> PortCalibration {
+        self.cache.port_calibration
+    }
+
+    /// Measure the power of samples in dBFS, with the offset of the
+    /// [active Opera Cake port] applied.
+    ///
+    /// The offsets are set with [`set_port_calibration`], no offset is
+    /// applied if the ports have not been set through this handle.
+    /// See [`power_dbfs`](crate::power_dbfs) for the measurement.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, OperacakePort, PortCalibration, RxMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut cal: PortCalibration = PortCalibration::default();
+    /// cal.set(OperacakePort::A2, 1.5);
+    /// radio.set_port_calibration(cal);
+    /// radio.set_operacake_ports(0, OperacakePort::A2, OperacakePort::B1)?;
+    /// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+    /// let data: Vec<u8> = radio.rx()?;
+    /// let dbfs: f32 = radio.power_dbfs(&data);
+    /// radio.stop_rx()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [active Opera Cake port]: crate::HackRfOne::operacake_port
+    /// [`set_port_calibration`]: crate::HackRfOne::set_port_calibration
+    pub fn power_dbfs(&self, buf: &[u8]) -> f32 {
+        self.cache
+            .port_calibration
+            .power_dbfs(self.cache.operacake_port, buf)
+    }
+
+    /// Returns `true` if an Opera Cake is connected.
+    ///
+    /// Unlike [`operacake_boards`] this does not fail on firmware or
+    /// hardware without Opera Cake support, it returns `false` instead.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// if radio.has_operacake() {
+    ///     // .. use the antenna switch
+    /// }
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`operacake_boards`]: crate::HackRfOne::operacake_boards
+    pub fn has_operacake(&self) -> bool {
+        self.operacake_boards()
+            .map(|boards| !boards.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// Perform a raw control IN transfer.
+    ///
+    /// All requests made by this crate are vendor requests to the device
+    /// recipient.
+    /// This allows any request type and recipient, for firmware forks that
+    /// use other requests, such as vendor requests to an interface.
+    ///
+    /// Returns the number of bytes read into `buf`.
+    ///
+    /// # Example
+    ///
+    /// Read the board ID with an interface recipient.
+    ///
+    /// ```no_run
+    /// use hackrfone::{
+    ///     rusb::{Recipient, RequestType},
+    ///     HackRfOne, UnknownMode,
+    /// };
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut buf: [u8; 1] = [0; 1];
+    /// radio.control_read(RequestType::Vendor, Recipient::Interface, 14, 0, 0, &mut buf)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn control_read(
+        &self,
+        kind: RequestType,
+        recipient: Recipient,
+        request: u8,
+        value: u16,
+        index: u16,
+        buf: &mut [u8],
+    ) -> Result<usize, Error> {
+        let result: Result<usize, rusb::Error> = self.dev.dh.read_control(
+            request_type(Direction::In, kind, recipient),
+            request,
+            value,
+            index,
+            buf,
+            self.to,
+        );
+        trace::raw_control(Direction::In, request, value, index, buf.len(), &result);
+        Ok(result?)
+    }
+
+    /// Perform a raw control OUT transfer.
+    ///
+    /// See [`control_read`] for details.
+    ///
+    /// Returns the number of bytes written from `buf`.
+    ///
+    /// # Example
+    ///
+    /// Turn the amplifier on with an interface recipient.
+    ///
+    /// ```no_run
+    /// use hackrfone::{
+    ///     rusb::{Recipient, RequestType},
+    ///     HackRfOne, UnknownMode,
+    /// };
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.control_write(RequestType::Vendor, Recipient::Interface, 17, 1, 0, &[])?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`control_read`]: crate::HackRfOne::control_read
+    pub fn control_write(
+        &mut self,
+        kind: RequestType,
+        recipient: Recipient,
+        request: u8,
+        value: u16,
+        index: u16,
+        buf: &[u8],
+    ) -> Result<usize, Error> {
+        let result: Result<usize, rusb::Error> = self.dev.dh.write_control(
+            request_type(Direction::Out, kind, recipient),
+            request,
+            value,
+            index,
+            buf,
+            self.to,
+        );
+        trace::raw_control(Direction::Out, request, value, index, buf.len(), &result);
+        Ok(result?)
+    }
+
+    /// Read a Si5351C clock generator register.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let status: u8 = radio.si5351c_read(0)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn si5351c_read(&self, register: u8) -> Result<u8, Error> {
+        let buf: [u8; 1] = self.read_control(Request::Si5351CRead, 0, register.into())?;
+        Ok(buf[0])
+    }
+
+    /// Write a Si5351C clock generator register.
+    ///
+    /// Writing registers directly can leave the clocks in a state the
+    /// firmware does not expect, reset the radio to recover.
+    pub fn si5351c_write(&mut self, register: u8, value: u8) -> Result<(), Error> {
+        self.write_control(Request::Si5351CWrite, value.into(), register.into(), &[])
+    }
+
+    /// Read a MAX2837 transceiver register.
+    ///
+    /// The registers are 10 bits wide.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if `register` is not below
+    /// [`MAX2837_NUM_REGISTERS`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let value: u16 = radio.max2837_read(0)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn max2837_read(&self, register: u8) -> Result<u16, Error> {
+        if usize::from(register) >= MAX2837_NUM_REGISTERS {
+            return Err(Error::Argument);
+        }
+        let buf: [u8; 2] = self.read_control(Request::Max2837Read, 0, register.into())?;
+        Ok(u16::from_le_bytes(buf))
+    }
+
+    /// Write a MAX2837 transceiver register.
+    ///
+    /// Writing registers directly can leave the transceiver in a state the
+    /// firmware does not expect, use [`reset_max2837`] to recover.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if `register` is not below
+    /// [`MAX2837_NUM_REGISTERS`] or `value` does not fit in 10 bits.
+    ///
+    /// [`reset_max2837`]: crate::HackRfOne::reset_max2837
+    pub fn max2837_write(&mut self, register: u8, value: u16) -> Result<(), Error> {
+        if usize::from(register) >= MAX2837_NUM_REGISTERS || value > 0x3FF {
+            return Err(Error::Argument);
+        }
+        self.write_control(Request::Max2837Write, value, register.into(), &[])
+    }
+
+    /// Write the power on values to all MAX2837 transceiver registers.
+    ///
+    /// This recovers from experiments with [`max2837_write`] without
+    /// resetting the device, the values are [`MAX2837_DEFAULT_REGISTERS`].
+    ///
+    /// Only the transceiver registers are written, the settings cached by
+    /// this handle and the other ICs are unchanged.
+    /// The transceiver synthesizer and gain registers are reset with the
+    /// rest, set the frequency and gains again before receiving.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.max2837_write(8, 0x3FF)?;
+    /// radio.reset_max2837()?;
+    /// radio.set_freq(915_000_000)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`max2837_write`]: crate::HackRfOne::max2837_write
+    pub fn reset_max2837(&mut self) -> Result<(), Error> {
+        for (register, &value) in (0..=u8::MAX).zip(MAX2837_DEFAULT_REGISTERS.iter()) {
+            self.max2837_write(register, value)?;
+        }
+        Ok(())
+    }
+
+    /// Read a RFFC5071 mixer register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if `register` is not below
+    /// [`RFFC5071_NUM_REGISTERS`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let value: u16 = radio.rffc5071_read(0)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn rffc5071_read(&self, register: u8) -> Result<u16, Error> {
+        if usize::from(register) >= RFFC5071_NUM_REGISTERS {
+            return Err(Error::Argument);
+        }
+        let buf: [u8; 2] = self.read_control(Request::Rffc5071Read, 0, register.into())?;
+        Ok(u16::from_le_bytes(buf))
+    }
+
+    /// Read all MAX2837, Si5351C, and RFFC5071 registers.
+    ///
+    /// This is intended for diagnosing tuning and sensitivity problems, the
+    /// [`RegisterDump`] prints one register per line.
+    ///
+    /// Each register is a separate control transfer, over 300 in total,
+    /// this typically takes a few hundred milliseconds.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, RegisterDump, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let dump: RegisterDump = radio.dump_registers()?;
+    /// println!("{dump}");
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn dump_registers(&self) -> Result<RegisterDump, Error> {
+        let mut dump: RegisterDump = RegisterDump {
+            max2837: [0; MAX2837_NUM_REGISTERS],
+            si5351c: [0; SI5351C_NUM_REGISTERS],
+            rffc5071: [0; RFFC5071_NUM_REGISTERS],
+        };
+        for (register, value) in (0..=u8::MAX).zip(dump.max2837.iter_mut()) {
+            *value = self.max2837_read(register)?;
+        }
+        for (register, value) in (0..=u8::MAX).zip(dump.si5351c.iter_mut()) {
+            *value = self.si5351c_read(register)?;
+        }
+        for (register, value) in (0..=u8::MAX).zip(dump.rffc5071.iter_mut()) {
+            *value = self.rffc5071_read(register)?;
+        }
+        Ok(dump)
+    }
+
+    /// Check if the Si5351C PLLs are locked.
+    ///
+    /// This reads the Si5351C device status register (register 0) and
+    /// returns `true` if the device has finished initializing (`SYS_INIT`,
+    /// bit 7) and neither PLL B (`LOL_B`, bit 6) nor PLL A (`LOL_A`, bit 5)
+    /// reports a loss of lock.
+    ///
+    /// When using an external reference clock, wait for the PLLs to lock
+    /// before trusting the tuned frequency.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// assert!(radio.pll_locked()?);
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn pll_locked(&self) -> Result<bool, Error> {
+        const SI5351C_DEVICE_STATUS: u8 = 0;
+        const SYS_INIT: u8 = 1 << 7;
+        const LOL_B: u8 = 1 << 6;
+        const LOL_A: u8 = 1 << 5;
+
+        let status: u8 = self.si5351c_read(SI5351C_DEVICE_STATUS)?;
+        Ok(status & (SYS_INIT | LOL_B | LOL_A) == 0)
+    }
+
+    /// Read the streaming status of the firmware.
+    ///
+    /// This is a health check for long running applications, shortfalls and
+    /// errors are reported without inferring them from the transfers.
+    ///
+    /// The status requires USB API version 1.0.6 (firmware 2022.09.1) or
+    /// later, all fields are available from that version.
+    /// The firmware resets the counters when streaming starts.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Version`] if the firmware does not support the
+    /// status request.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{DeviceStatus, HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let status: DeviceStatus = radio.read_status()?;
+    /// if status.num_shortfalls > 0 {
+    ///     eprintln!("{} shortfalls", status.num_shortfalls);
+    /// }
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn read_status(&self) -> Result<DeviceStatus, Error> {
+        self.check_api_version(API_STATUS)?;
+        let buf: [u8; M0_STATE_SIZE] = self.read_control(Request::GetM0State, 0, 0)?;
+        let mut status: DeviceStatus = DeviceStatus::parse(&buf);
+        status.num_shortfalls =
+            shortfalls_since(status.num_shortfalls, self.cache.shortfall_baseline);
+        Ok(status)
+    }
+
+    /// Clear the shortfall count reported by [`read_status`].
+    ///
+    /// The firmware has no request to clear its status, this records the
+    /// current shortfall count on the host and later reads report the
+    /// shortfalls since.
+    /// The other fields are reported as is.
+    /// The recorded count is dropped when streaming starts, like the
+    /// firmware counters.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Version`] if the firmware does not support the
+    /// status request.
+    ///
+    /// [`read_status`]: crate::HackRfOne::read_status
+    pub fn clear_status(&mut self) -> Result<(), Error> {
+        self.cache.shortfall_baseline = None;
+        let status: DeviceStatus = self.read_status()?;
+        self.cache.shortfall_baseline = Some(status.num_shortfalls);
+        Ok(())
+    }
+
+    /// Reset the HackRF radio.
+    ///
+    /// The device re-enumerates on the USB bus after a reset, which leaves
+    /// the returned handle stale.
+    /// Use [`reset_and_reopen`] to get a handle that remains usable.
+    ///
+    /// The transceiver is turned off and the streaming endpoint halt is
+    /// cleared before the reset is requested, some firmware versions hang on
+    /// a reset while streaming.
+    /// These steps are best-effort, errors from them are ignored and the
+    /// reset is always requested, so a device stuck streaming can still be
+    /// reset.
+    /// Only the endpoint this handle streams on is cleared; from
+    /// [`UnknownMode`] the transceiver is turned off but no halt is cleared,
+    /// the device clears any halt as it resets.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<UnknownMode> = radio.reset()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`reset_and_reopen`]: crate::HackRfOne::reset_and_reopen
+    pub fn reset(mut self) -> Result<HackRfOne<UnknownMode>, Error> {
+        self.check_api_version(API_RESET)?;
+        let endpoint: Option<u8> = self.dev.tm.endpoint();
+        reset_sequence(|step| match step {
+            ResetStep::Off => self.set_transceiver_mode(TranscieverMode::Off),
+            ResetStep::ClearHalt => match endpoint {
+                Some(endpoint) => Ok(self.dev.dh.clear_halt(endpoint)?),
+                None => Ok(()),
+            },
+            ResetStep::Release => match endpoint {
+                Some(_) => Ok(self.dev.dh.release_interface(0)?),
+                None => Ok(()),
+            },
+            ResetStep::Reset => self.write_control(Request::Reset, 0, 0, &[]),
+        })?;
+        // the device is off after the reset, even if turning it off failed
+        self.dev.tm = TranscieverMode::Off;
+        Ok(self.with_mode(UnknownMode))
+    }
+
+    /// Reset the HackRF radio and re-open it once it re-enumerates.
+    ///
+    /// After a reset the device drops off the USB bus and comes back with a
+    /// new address, which makes the handle returned by [`reset`] stale.
+    /// This method waits for the device to disappear, then waits for a device
+    /// with the same serial number to re-appear and opens it.
+    ///
+    /// The transfer timeout set with [`set_timeout`] is carried over to the
+    /// new handle.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Usb(rusb::Error::Timeout)` if the device does not
+    /// disappear and re-appear within `timeout`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, UnknownMode};
+    /// use std::time::Duration;
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<UnknownMode> = radio.reset_and_reopen(Duration::from_secs(5))?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`reset`]: crate::HackRfOne::reset
+    /// [`set_timeout`]: crate::HackRfOne::set_timeout
+    pub fn reset_and_reopen(self, timeout: Duration) -> Result<HackRfOne<UnknownMode>, Error> {
+        const POLL: Duration = Duration::from_millis(50);
+
+        let start: Instant = Instant::now();
+        let serial: [u32; 4] = self.serial_number()?;
+        let to: Duration = self.to;
+        let stale: HackRfOne<UnknownMode> = self.reset()?;
+
+        // the old handle stops responding once the device leaves the bus
+        while stale.board_id().is_ok() {
+            if start.elapsed() >= timeout {
+                return Err(Error::Usb(rusb::Error::Timeout));
+            }
+            thread::sleep(POLL);
+        }
+        drop(stale);
+
+        loop {
+            let radio: Option<HackRfOne<UnknownMode>> =
+                HackRfOne::open_first(|radio| radio.serial_number() == Ok(serial));
+            if let Some(mut radio) = radio {
+                radio.to = to;
+                return Ok(radio);
+            }
+            if start.elapsed() >= timeout {
+                return Err(Error::Usb(rusb::Error::Timeout));
+            }
+            thread::sleep(POLL);
+        }
+    }
+
+    /// Recover a wedged device by resetting and re-opening it.
+    ///
+    /// When the firmware stops responding to streaming requests, for example
+    /// after repeated pipe errors, a reset is often the only fix.
+    /// This resets the device and re-opens it with [`reset_and_reopen`],
+    /// waiting up to 5 seconds for the device to re-enumerate.
+    /// Like [`reset`], streaming is stopped before the reset ignoring errors,
+    /// the reset request is sent even if the device does not respond to the
+    /// request to stop.
+    /// A reset usually takes about one second.
+    ///
+    /// The device is matched by serial number, the serial number must be
+    /// readable before the reset, and the device must come back with the same
+    /// serial number.
+    /// Settings are not restored, the returned handle starts from the
+    /// firmware defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the serial number cannot be read or the reset
+    /// request fails, and `Error::Usb(rusb::Error::Timeout)` if the device
+    /// does not re-appear in time.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, RxMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+    /// if radio.rx().is_err() && radio.rx().is_err() {
+    ///     let radio: HackRfOne<UnknownMode> = radio.recover()?;
+    /// }
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`reset`]: crate::HackRfOne::reset
+    /// [`reset_and_reopen`]: crate::HackRfOne::reset_and_reopen
+    pub fn recover(self) -> Result<HackRfOne<UnknownMode>, Error> {
+        const TIMEOUT: Duration = Duration::from_secs(5);
+
+        self.reset_and_reopen(TIMEOUT)
+    }
+
+    fn set_transceiver_mode(&mut self, mode: TranscieverMode) -> Result<(), Error> {
+        self.write_control(Request::SetTransceiverMode, mode.into(), 0, &[])?;
+        self.dev.tm = mode;
+        Ok(())
+    }
+
+    // All mode transitions go through stop_streaming and start_streaming,
+    // the transceiver always passes through Off between modes.
+
+    /// Turn the transceiver off and release the streaming endpoint.
+    ///
+    /// Data still buffered on the endpoint is discarded.
+    /// Nothing is cleared or released if this handle is not streaming, even
+    /// if another handle left the device streaming.
+    fn stop_streaming(&mut self) -> Result<(), Error> {
+        let endpoint: Option<u8> = self.dev.tm.endpoint();
+        self.set_transceiver_mode(TranscieverMode::Off)?;
+        if let Some(endpoint) = endpoint {
+            self.dev.dh.clear_halt(endpoint)?;
+            self.dev.dh.release_interface(0)?;
+        }
+        Ok(())
+    }
+
+    /// Claim the streaming endpoint and turn the transceiver on.
+    ///
+    /// The transceiver must be off, stale data left over from a previous
+    /// session is discarded.
+    fn start_streaming<NEW>(
+        mut self,
+        mode: TranscieverMode,
+        new: NEW,
+    ) -> Result<HackRfOne<NEW>, Error> {
+        if let Some(endpoint) = mode.endpoint() {
+            self.dev.dh.claim_interface(0)?;
+            self.dev.dh.clear_halt(endpoint)?;
+            // the firmware resets its counters when streaming starts
+            self.cache.shortfall_baseline = None;
+        }
+        self.set_transceiver_mode(mode)?;
+        Ok(self.with_mode(new))
+    }
+
+    fn transition<NEW>(mut self, mode: TranscieverMode, new: NEW) -> Result<HackRfOne<NEW>, Error> {
+        self.stop_streaming()?;
+        if mode == TranscieverMode::Off {
+            // already off, avoid sending the same request twice
+            Ok(self.with_mode(new))
+        } else {
+            self.start_streaming(mode, new)
+        }
+    }
+
+    /// Change the radio mode to RX.
+    ///
+    /// The radio is stopped first if it is in another mode.
+    /// The RX endpoint is cleared before receiving starts, so no stale data
+    /// from a previous session is returned by [`rx`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InsufficientBandwidth`] if the negotiated USB speed
+    /// cannot sustain the sample rate set with [`set_sample_rate`], for
+    /// example a full speed (USB 1.1) port.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, RxMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`rx`]: crate::HackRfOne::rx
+    /// [`set_sample_rate`]: crate::HackRfOne::set_sample_rate
+    pub fn into_rx_mode(self) -> Result<HackRfOne<RxMode>, Error> {
+        self.check_usb_bandwidth()?;
+        self.transition(TranscieverMode::Receive, RxMode)
+    }
+
+    /// Change the radio mode to TX.
+    ///
+    /// The radio is stopped first if it is in another mode.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, TxMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<TxMode> = radio.into_tx_mode()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn into_tx_mode(self) -> Result<HackRfOne<TxMode>, Error> {
+        self.transition(TranscieverMode::Transmit, TxMode)
+    }
+
+    /// Change the radio mode to sweep.
+    ///
+    /// The radio is stopped first if it is in another mode.
+    /// The antenna port power (bias-tee) and RF amplifier are set from the
+    /// configuration before the sweep starts, so the sweep never runs with
+    /// them in an unknown state.
+    /// This disables automatic amplifier control, see [`set_amp_auto`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if the configuration is invalid, see
+    /// [`SweepConfig`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, SweepConfig, SweepMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let config: SweepConfig = SweepConfig {
+    ///     ranges: vec![(2400, 2500)],
+    ///     bias_tee: true,
+    ///     ..Default::default()
+    /// };
+    /// let mut radio: HackRfOne<SweepMode> = radio.into_sweep_mode(&config)?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`set_amp_auto`]: crate::HackRfOne::set_amp_auto
+    pub fn into_sweep_mode(mut self, config: &SweepConfig) -> Result<HackRfOne<SweepMode>, Error> {
+        self.check_api_version(API_SWEEP)?;
+        let buf: Vec<u8> = sweep_params(config)?;
+
+        self.stop_streaming()?;
+        self.set_antenna_enable(config.bias_tee.into())?;
+        self.set_amp_enable(config.amp)?;
+        self.write_control(
+            Request::InitSweep,
+            (config.num_bytes & 0xFFFF) as u16,
+            (config.num_bytes >> 16) as u16,
+            &buf,
+        )?;
+        self.start_streaming(TranscieverMode::RxSweep, SweepMode)
+    }
+}
+
+impl HackRfOne<RxMode> {
+    /// Receive data from the radio.
+    ///
+    /// This uses a bulk transfer to get one MTU (maximum transmission unit)
+    /// of data in a single shot.  The data format is pairs of signed 8-bit IQ.
+    /// Use the [`iq_to_cplx_i8`] or [`iq_to_cplx_f32`] helpers to convert the
+    /// data to a more manageable format.
+    ///
+    /// Unlike `libhackrf` this does not spawn a sampling thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, RxMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+    /// let data: Vec<u8> = radio.rx()?;
+    /// radio.stop_rx()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`iq_to_cplx_i8`]: crate::iq_to_cplx_i8
+    /// [`iq_to_cplx_f32`]: crate::iq_to_cplx_f32
+    #[cfg_attr(not(feature = "num-complex"), allow(rustdoc::broken_intra_doc_links))]
+    pub fn rx(&mut self) -> Result<Vec<u8>, Error> {
+        let mut buf: Vec<u8> = vec![0; self.rx_mtu];
+        let n: usize = self.read_bulk(&mut buf)?;
+        buf.truncate(n);
+        Ok(buf)
+    }
+
+    /// Receive data from the radio with a host timestamp.
+    ///
+    /// This is the same as [`rx`], and also returns the time the bulk
+    /// transfer completed.
+    ///
+    /// The timestamp is taken on the host after the transfer returns, it is
+    /// approximate; it includes USB and scheduling latency, and is only
+    /// accurate to roughly one transfer.
+    /// It is not a sample accurate hardware timestamp.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, RxMode, UnknownMode};
+    /// use std::time::Instant;
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+    /// let (time, data): (Instant, Vec<u8>) = radio.rx_timed()?;
+    /// radio.stop_rx()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`rx`]: crate::HackRfOne::rx
+    pub fn rx_timed(&mut self) -> Result<(Instant, Vec<u8>), Error> {
+        let data: Vec<u8> = self.rx()?;
+        Ok((Instant::now(), data))
+    }
+
+    /// Receive data from the radio into a buffer.
+    ///
+    /// This is the same as [`rx`], but it receives into a caller provided
+    /// buffer instead of allocating.
+    /// The buffer length should be a multiple of [`USB_PACKET_SIZE`],
+    /// a typical size is [`RX_MTU_DEFAULT`].
+    ///
+    /// Returns the number of bytes received.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, RxMode, UnknownMode, RX_MTU_DEFAULT};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+    /// let mut buf: Vec<u8> = vec![0; RX_MTU_DEFAULT];
+    /// let n: usize = radio.rx_into(&mut buf)?;
+    /// radio.stop_rx()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`rx`]: crate::HackRfOne::rx
+    pub fn rx_into(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        Ok(self.read_bulk(buf)?)
+    }
+
+    /// Receive data from the radio as complex samples.
+    ///
+    /// This is [`rx`] followed by [`samples_to_cplx_f32`].
+    /// A trailing odd byte is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{num_complex::Complex, HackRfOne, RxMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+    /// let samples: Vec<Complex<f32>> = radio.rx_cplx_f32()?;
+    /// radio.stop_rx()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`rx`]: crate::HackRfOne::rx
+    #[cfg(feature = "num-complex")]
+    pub fn rx_cplx_f32(&mut self) -> Result<Vec<num_complex::Complex<f32>>, Error> {
+        Ok(samples_to_cplx_f32(&self.rx()?))
+    }
+
+    /// Start a queued RX stream.
+    ///
+    /// The stream uses the [transfer count] and [transfer size] configured on
+    /// the radio.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, RxMode, RxStream, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+    /// let stream: RxStream = radio.into_stream();
+    /// let data: Vec<u8> = stream.recv()?;
+    /// stream.stop().stop_rx()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [transfer count]: crate::HackRfOne::set_transfer_count
+    /// [transfer size]: crate::HackRfOne::set_transfer_size
+    pub fn into_stream(self) -> RxStream {
+        RxStream::new(self)
+    }
+
+    /// Stop receiving.
+    ///
+    /// Any data still buffered on the RX endpoint is discarded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, RxMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+    /// let data: Vec<u8> = radio.rx()?;
+    /// radio.stop_rx()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn stop_rx(self) -> Result<HackRfOne<UnknownMode>, Error> {
+        self.transition(TranscieverMode::Off, UnknownMode)
+    }
+}
+
+impl HackRfOne<TxMode> {
+    /// Transmit data with the radio.
+    ///
+    /// This uses a single bulk transfer.
+    /// The data format is pairs of signed 8-bit IQ, the same as [`rx`].
+    ///
+    /// The length of `buf` should be a multiple of [`USB_PACKET_SIZE`],
+    /// otherwise the final partial packet may not be transmitted; use
+    /// [`tx_padded`] for buffers of arbitrary length.
+    ///
+    /// Returns the number of bytes transferred.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{generate_tone, HackRfOne, TxMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_sample_rate(20_000_000, 2)?;
+    /// radio.set_freq(915_000_000)?;
+    /// let mut radio: HackRfOne<TxMode> = radio.into_tx_mode()?;
+    /// radio.tx(&generate_tone(10_000_000, 100_000.0, 64 * 1024, 127))?;
+    /// radio.stop_tx()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`rx`]: crate::HackRfOne::rx
+    /// [`tx_padded`]: crate::HackRfOne::tx_padded
+    pub fn tx(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        Ok(self.write_bulk(buf)?)
+    }
+
+    /// Transmit data with the radio, padding to a whole USB packet.
+    ///
+    /// If the length of `buf` is not a multiple of [`USB_PACKET_SIZE`] it is
+    /// padded with zero IQ samples up to the next packet boundary.
+    /// This appends a brief zero-signal tail, at most 255 samples, to the
+    /// transmission.
+    ///
+    /// Returns the number of bytes transferred, including padding.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, TxMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<TxMode> = radio.into_tx_mode()?;
+    /// assert_eq!(radio.tx_padded(&[127, 0, 127, 0])?, 512);
+    /// radio.stop_tx()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn tx_padded(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let len: usize = padded_len(buf.len());
+        if len == buf.len() {
+            self.tx(buf)
+        } else {
+            let mut padded: Vec<u8> = Vec::with_capacity(len);
+            padded.extend_from_slice(buf);
+            padded.resize(len, 0);
+            self.tx(&padded)
+        }
+    }
+
+    /// Transmit a preamble followed by a payload.
+    ///
+    /// Keying up the transmitter directly at full amplitude produces
+    /// spectral splatter while the amplifier settles.
+    /// Sending a preamble that ramps up the amplitude first avoids these out
+    /// of band emissions.
+    ///
+    /// If `preamble` is empty a default preamble of 256 samples is used, the
+    /// first payload sample with its amplitude ramped up from zero along a
+    /// raised cosine, so the transmission starts smoothly and joins the
+    /// payload without a discontinuity.
+    ///
+    /// The data is padded as with [`tx_padded`], the number of bytes
+    /// transferred is returned.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{generate_tone, HackRfOne, TxMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<TxMode> = radio.into_tx_mode()?;
+    /// let payload: Vec<u8> = generate_tone(10_000_000, 100_000.0, 4096, 127);
+    /// radio.tx_with_preamble(&[], &payload)?;
+    /// radio.stop_tx()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`tx_padded`]: crate::HackRfOne::tx_padded
+    pub fn tx_with_preamble(&mut self, preamble: &[u8], payload: &[u8]) -> Result<usize, Error> {
+        let mut buf: Vec<u8> = if preamble.is_empty() {
+            ramp_preamble(payload, TX_RAMP_SAMPLES)
+        } else {
+            preamble.to_vec()
+        };
+        buf.extend_from_slice(payload);
+        self.tx_padded(&buf)
+    }
+
+    /// Transmit the contents of a file.
+    ///
+    /// The file is raw interleaved signed 8-bit IQ without a header, the
+    /// same format as a [`Recorder`] writes with [`SampleFormat::Cs8`].
+    /// It is streamed to the radio in bulk transfers of the
+    /// [transfer size], a final partial transfer is padded with zero
+    /// samples to a whole USB packet, see [`tx_padded`].
+    /// Files written by [`write_cs8`] have a header, use [`read_cs8`] and
+    /// [`tx_padded`] for those instead.
+    ///
+    /// Returns the number of bytes transferred, including padding.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, TxMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// radio.set_sample_rate(20_000_000, 2)?;
+    /// radio.set_freq(915_000_000)?;
+    /// let mut radio: HackRfOne<TxMode> = radio.into_tx_mode()?;
+    /// let sent: u64 = radio.transmit_file("capture.raw")?;
+    /// radio.stop_tx()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [transfer size]: crate::HackRfOne::set_transfer_size
+    /// [`tx_padded`]: crate::HackRfOne::tx_padded
+    pub fn transmit_file<P: AsRef<Path>>(&mut self, path: P) -> Result<u64, Error> {
+        let mut file: File = File::open(path)?;
+        let mut buf: Vec<u8> = vec![0; self.transfer_size];
+        let mut sent: u64 = 0;
+        loop {
+            let n: usize = read_full(&mut file, &mut buf)?;
+            if n == 0 {
+                break;
+            }
+            sent += self.tx_padded(&buf[..n])? as u64;
+            if n < buf.len() {
+                break;
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Stop transmitting.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, TxMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<TxMode> = radio.into_tx_mode()?;
+    /// let radio: HackRfOne<UnknownMode> = radio.stop_tx()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn stop_tx(self) -> Result<HackRfOne<UnknownMode>, Error> {
+        self.transition(TranscieverMode::Off, UnknownMode)
+    }
+}
+
+impl HackRfOne<SweepMode> {
+    /// Receive sweep blocks from the radio.
+    ///
+    /// This uses a bulk transfer of the [transfer size] and returns the
+    /// blocks in it as `(frequency, samples)` pairs.
+    /// The frequency is the center frequency in Hz the block was received at,
+    /// the samples are pairs of signed 8-bit IQ without the block header.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, SweepConfig, SweepMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<SweepMode> = radio.into_sweep_mode(&SweepConfig::default())?;
+    /// for (freq, samples) in radio.read_sweep()? {
+    ///     // .. do whatever you want with the samples here
+    /// }
+    /// radio.stop_sweep()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [transfer size]: crate::HackRfOne::set_transfer_size
+    pub fn read_sweep(&mut self) -> Result<Vec<(u64, Vec<u8>)>, Error> {
+        let mut buf: Vec<u8> = vec![0; self.transfer_size];
+        let n: usize = self.read_bulk(&mut buf)?;
+        Ok(buf[..n]
+            .chunks_exact(SWEEP_BLOCK_SIZE)
+            .filter_map(parse_sweep_block)
+            .map(|(freq, samples)| (freq, samples.to_vec()))
+            .collect())
+    }
+
+    /// Receive sweep blocks continuously.
+    ///
+    /// This calls [`read_sweep`] as needed and yields the blocks one at a
+    /// time, which suits a live spectrum display driven by a `for` loop.
+    /// The firmware keeps sweeping the configured ranges until the sweep is
+    /// stopped, so the iterator never ends by itself.
+    ///
+    /// After an error is yielded the iterator ends; the sweep can be stopped
+    /// with [`stop_sweep`], or resumed by calling this method again.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, SweepConfig, SweepMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<SweepMode> = radio.into_sweep_mode(&SweepConfig::default())?;
+    /// for block in radio.sweep_forever().take(1000) {
+    ///     let (freq, samples): (u64, Vec<u8>) = block?;
+    ///     // .. do whatever you want with the samples here
+    /// }
+    /// radio.stop_sweep()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    ///
+    /// [`read_sweep`]: crate::HackRfOne::read_sweep
+    /// [`stop_sweep`]: crate::HackRfOne::stop_sweep
+    pub fn sweep_forever(&mut self) -> impl Iterator<Item = Result<(u64, Vec<u8>), Error>> + '_ {
+        let mut pending: std::vec::IntoIter<(u64, Vec<u8>)> = Vec::new().into_iter();
+        let mut failed: bool = false;
+        std::iter::from_fn(move || loop {
+            if failed {
+                return None;
+            }
+            if let Some(block) = pending.next() {
+                return Some(Ok(block));
+            }
+            match self.read_sweep() {
+                Ok(blocks) => pending = blocks.into_iter(),
+                Err(e) => {
+                    failed = true;
+                    return Some(Err(e));
+                }
+            }
+        })
+    }
+
+    /// Stop sweeping.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hackrfone::{HackRfOne, SweepConfig, SweepMode, UnknownMode};
+    ///
+    /// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+    /// let mut radio: HackRfOne<SweepMode> = radio.into_sweep_mode(&SweepConfig::default())?;
+    /// let radio: HackRfOne<UnknownMode> = radio.stop_sweep()?;
+    /// # Ok::<(), hackrfone::Error>(())
+    /// ```
+    pub fn stop_sweep(self) -> Result<HackRfOne<UnknownMode>, Error> {
+        self.transition(TranscieverMode::Off, UnknownMode)
+    }
+}
+
+// Helper for into_sweep_mode
+fn sweep_params(config: &SweepConfig) -> Result<Vec<u8>, Error> {
+    let block_size: u32 = SWEEP_BLOCK_SIZE as u32;
+    if config.ranges.is_empty()
+        || config.ranges.len() > SWEEP_MAX_RANGES
+        || config.num_bytes < block_size
+        || !config.num_bytes.is_multiple_of(block_size)
+    {
+        return Err(Error::Argument);
+    }
+
+    let mut buf: Vec<u8> = Vec::with_capacity(9 + config.ranges.len() * 4);
+    buf.extend_from_slice(&config.step_width.to_le_bytes());
+    buf.extend_from_slice(&config.offset.to_le_bytes());
+    buf.push(config.style as u8);
+    config.ranges.iter().for_each(|(start, stop)| {
+        buf.extend_from_slice(&start.to_le_bytes());
+        buf.extend_from_slice(&stop.to_le_bytes());
+    });
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod sweep_params {
+    use super::{sweep_params, Error, SweepConfig, SweepStyle};
+
+    #[test]
+    fn nominal() {
+        let config: SweepConfig = SweepConfig {
+            ranges: vec![(2400, 2500), (1, 6000)],
+            num_bytes: 16384,
+            step_width: 20_000_000,
+            offset: 7_500_000,
+            style: SweepStyle::Interleaved,
+            bias_tee: false,
+            amp: false,
+        };
+        assert_eq!(
+            sweep_params(&config),
+            Ok(vec![
+                0x00, 0x2D, 0x31, 0x01, // step width
+                0xE0, 0x70, 0x72, 0x00, // offset
+                0x01, // style
+                0x60, 0x09, 0xC4, 0x09, // 2400 - 2500
+                0x01, 0x00, 0x70, 0x17, // 1 - 6000
+            ])
+        );
+    }
+
+    #[test]
+    fn ranges() {
+        let mut config: SweepConfig = SweepConfig {
+            ranges: vec![],
+            ..Default::default()
+        };
+        assert_eq!(sweep_params(&config), Err(Error::Argument));
+        config.ranges = vec![(1, 2); 11];
+        assert_eq!(sweep_params(&config), Err(Error::Argument));
+        config.ranges = vec![(1, 2); 10];
+        assert!(sweep_params(&config).is_ok());
+    }
+
+    #[test]
+    fn num_bytes() {
+        let mut config: SweepConfig = SweepConfig {
+            num_bytes: 0,
+            ..Default::default()
+        };
+        assert_eq!(sweep_params(&config), Err(Error::Argument));
+        config.num_bytes = 16385;
+        assert_eq!(sweep_params(&config), Err(Error::Argument));
+        config.num_bytes = 2 * 16384;
+        assert!(sweep_params(&config).is_ok());
+    }
+}
+
+// Helper for read_sweep, returns the frequency and samples of a block
+fn parse_sweep_block(block: &[u8]) -> Option<(u64, &[u8])> {
+    match block {
+        [0x7F, 0x7F, f0, f1, f2, f3, f4, f5, f6, f7, samples @ ..] => Some((
+            u64::from_le_bytes([*f0, *f1, *f2, *f3, *f4, *f5, *f6, *f7]),
+            samples,
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod parse_sweep_block {
+    use super::parse_sweep_block;
+
+    #[test]
+    fn nominal() {
+        let block: [u8; 12] = [0x7F, 0x7F, 0x40, 0x4B, 0x4C, 0x00, 0, 0, 0, 0, 1, 2];
+        assert_eq!(parse_sweep_block(&block), Some((5_000_000, &[1, 2][..])));
+    }
+
+    #[test]
+    fn bad_header() {
+        let block: [u8; 12] = [0x7F, 0x7E, 0x40, 0x4B, 0x4C, 0x00, 0, 0, 0, 0, 1, 2];
+        assert_eq!(parse_sweep_block(&block), None);
+    }
+
+    #[test]
+    fn short() {
+        assert_eq!(parse_sweep_block(&[0x7F, 0x7F, 0]), None);
+    }
+}
+
+// Helper for check_usb_bandwidth
+fn check_bandwidth(required_mbps: Option<f64>, speed: rusb::Speed) -> Result<(), Error> {
+    match (required_mbps, bulk_rate_mbps(speed)) {
+        (Some(required), Some(available)) if required > available => {
+            Err(Error::InsufficientBandwidth { speed })
+        }
+        _ => Ok(()),
+    }
+}
+
+// Helper for check_bandwidth, highest bulk payload rate of a USB link, the
+// signalling rate less the protocol overhead
+fn bulk_rate_mbps(speed: rusb::Speed) -> Option<f64> {
+    match speed {
+        // bulk transfers are not allowed on low speed links
+        rusb::Speed::Low => Some(0.0),
+        // 19 packets of 64 bytes per 1 ms frame
+        rusb::Speed::Full => Some(9.728),
+        // 13 packets of 512 bytes per 125 us microframe
+        rusb::Speed::High => Some(425.984),
+        // 8b/10b and 128b/132b line coding
+        rusb::Speed::Super => Some(4_000.0),
+        rusb::Speed::SuperPlus => Some(9_696.0),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod check_bandwidth {
+    use super::{check_bandwidth, Error};
+    use rusb::Speed;
+
+    #[test]
+    fn high_speed() {
+        // 20 MS/s needs 320 Mbit/s
+        assert_eq!(check_bandwidth(Some(320.0), Speed::High), Ok(()));
+        assert_eq!(
+            check_bandwidth(Some(480.0), Speed::High),
+            Err(Error::InsufficientBandwidth { speed: Speed::High })
+        );
+    }
+
+    #[test]
+    fn full_speed() {
+        // 2 MS/s needs 32 Mbit/s
+        assert_eq!(
+            check_bandwidth(Some(32.0), Speed::Full),
+            Err(Error::InsufficientBandwidth { speed: Speed::Full })
+        );
+        // signalling rate, but not payload rate
+        assert_eq!(
+            check_bandwidth(Some(12.0), Speed::Full),
+            Err(Error::InsufficientBandwidth { speed: Speed::Full })
+        );
+        assert_eq!(check_bandwidth(Some(8.0), Speed::Full), Ok(()));
+    }
+
+    #[test]
+    fn low_speed() {
+        assert_eq!(
+            check_bandwidth(Some(0.5), Speed::Low),
+            Err(Error::InsufficientBandwidth { speed: Speed::Low })
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(check_bandwidth(Some(320.0), Speed::Unknown), Ok(()));
+        assert_eq!(check_bandwidth(None, Speed::Full), Ok(()));
+    }
+}
+
+// Helper for set_baseband_filter_bandwidth, matches the firmware rounding
+fn baseband_filter_bw_round_up(hz: u32) -> Option<u32> {
+    BASEBAND_FILTER_BW.iter().copied().find(|&bw| bw >= hz)
+}
+
+#[cfg(test)]
+mod firmware_build_info {
+    use super::FirmwareBuildInfo;
+
+    #[test]
+    fn release() {
+        assert_eq!(
+            FirmwareBuildInfo::parse("2021.03.1"),
+            FirmwareBuildInfo {
+                release: Some("2021.03.1".into()),
+                git_hash: None,
+                dirty: false,
+            }
+        );
+    }
+
+    #[test]
+    fn git() {
+        assert_eq!(
+            FirmwareBuildInfo::parse("git-44df9d1"),
+            FirmwareBuildInfo {
+                release: None,
+                git_hash: Some("44df9d1".into()),
+                dirty: false,
+            }
+        );
+    }
+
+    #[test]
+    fn git_dirty() {
+        assert_eq!(
+            FirmwareBuildInfo::parse("git-44df9d1-dirty"),
+            FirmwareBuildInfo {
+                release: None,
+                git_hash: Some("44df9d1".into()),
+                dirty: true,
+            }
+        );
+    }
+
+    #[test]
+    fn release_and_git() {
+        assert_eq!(
+            FirmwareBuildInfo::parse("2023.01.1+git-a1b2c3d"),
+            FirmwareBuildInfo {
+                release: Some("2023.01.1".into()),
+                git_hash: Some("a1b2c3d".into()),
+                dirty: false,
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod rf_gain {
+    use super::{Error, RfGain};
+
+    #[test]
+    fn snap() {
+        let gain: RfGain = RfGain::new(true, 23, 33).unwrap();
+        assert!(gain.amp());
+        assert_eq!(gain.lna_db(), 16);
+        assert_eq!(gain.vga_db(), 32);
+    }
+
+    #[test]
+    fn limits() {
+        assert_eq!(RfGain::new(false, 40, 62).unwrap().lna_db(), 40);
+        assert_eq!(RfGain::new(false, 40, 62).unwrap().vga_db(), 62);
+        assert_eq!(RfGain::new(false, 41, 0), Err(Error::Argument));
+        assert_eq!(RfGain::new(false, 0, 63), Err(Error::Argument));
+    }
+
+    #[test]
+    fn compare() {
+        assert_eq!(RfGain::new(false, 17, 21), RfGain::new(false, 16, 20));
+        assert_eq!(RfGain::new(false, 0, 0), Ok(RfGain::default()));
+    }
+}
+
+#[cfg(test)]
+mod register_dump {
+    use super::{
+        RegisterDump, MAX2837_NUM_REGISTERS, RFFC5071_NUM_REGISTERS, SI5351C_NUM_REGISTERS,
+    };
+
+    #[test]
+    fn display() {
+        let mut dump: RegisterDump = RegisterDump {
+            max2837: [0; MAX2837_NUM_REGISTERS],
+            si5351c: [0; SI5351C_NUM_REGISTERS],
+            rffc5071: [0; RFFC5071_NUM_REGISTERS],
+        };
+        dump.max2837[0] = 0x150;
+        dump.si5351c[255] = 0xAB;
+        dump.rffc5071[30] = 0x1234;
+
+        let text: String = dump.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines.len(),
+            MAX2837_NUM_REGISTERS + SI5351C_NUM_REGISTERS + RFFC5071_NUM_REGISTERS
+        );
+        assert_eq!(lines[0], "MAX2837 0x00: 0x150");
+        assert_eq!(lines[MAX2837_NUM_REGISTERS], "Si5351C 0x00: 0x00");
+        assert_eq!(
+            lines[MAX2837_NUM_REGISTERS + SI5351C_NUM_REGISTERS - 1],
+            "Si5351C 0xFF: 0xAB"
+        );
+        assert_eq!(lines.last(), Some(&"RFFC5071 0x1E: 0x1234"));
+    }
+}
+
+#[cfg(test)]
+mod device_status {
+    use super::{DeviceStatus, M0_STATE_SIZE};
+
+    #[test]
+    fn parse() {
+        let mut buf: [u8; M0_STATE_SIZE] = [0; M0_STATE_SIZE];
+        buf[0..4].copy_from_slice(&[0x02, 0x00, 0x01, 0x00]);
+        for (idx, word) in buf[4..].chunks_exact_mut(4).enumerate() {
+            word.copy_from_slice(&(idx as u32 + 1).to_le_bytes());
+        }
+        assert_eq!(
+            DeviceStatus::parse(&buf),
+            DeviceStatus {
+                mode: 1,
+                m0_count: 2,
+                m4_count: 3,
+                num_shortfalls: 4,
+                longest_shortfall: 5,
+                error: 9,
+            }
+        );
+    }
+}
+
+// Helper for SPI flash requests, the address is split across the value and
+// index fields
+fn split_flash_address(addr: u32) -> Result<(u16, u16), Error> {
+    if addr as usize >= SPIFLASH_SIZE {
+        Err(Error::Argument)
+    } else {
+        Ok(((addr >> 16) as u16, (addr & 0xFFFF) as u16))
+    }
+}
+
+#[cfg(test)]
+mod split_flash_address {
+    use super::{split_flash_address, Error, SPIFLASH_SIZE};
+
+    #[test]
+    fn split() {
+        assert_eq!(split_flash_address(0), Ok((0, 0)));
+        assert_eq!(split_flash_address(0xFFFF), Ok((0, 0xFFFF)));
+        assert_eq!(split_flash_address(0x1_0000), Ok((1, 0)));
+        assert_eq!(split_flash_address(0xF_1234), Ok((0xF, 0x1234)));
+    }
+
+    #[test]
+    fn last() {
+        assert_eq!(
+            split_flash_address(SPIFLASH_SIZE as u32 - 1),
+            Ok((0xF, 0xFFFF))
+        );
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert_eq!(
+            split_flash_address(SPIFLASH_SIZE as u32),
+            Err(Error::Argument)
+        );
+        assert_eq!(split_flash_address(u32::MAX), Err(Error::Argument));
+    }
+}
+
+// Helper for configure_channel
+fn channel_plan(channel_bw_hz: u32, max_sample_rate: u32) -> Option<ChannelPlan> {
+    let channel_rate: u32 = channel_bw_hz.checked_mul(2).filter(|&r| r > 0)?;
+    let decimation: u32 = SAMPLE_RATE_MIN_HZ.div_ceil(channel_rate).max(1);
+    let sample_rate: u32 = channel_rate * decimation;
+    if sample_rate > max_sample_rate {
+        return None;
+    }
+    Some(ChannelPlan {
+        sample_rate,
+        baseband_filter_bw: baseband_filter_bw_round_up(channel_bw_hz.max(BASEBAND_FILTER_BW[0]))?,
+        decimation,
+        channel_rate,
+    })
+}
+
+#[cfg(test)]
+mod channel_plan {
+    use super::{channel_plan, ChannelPlan};
+
+    #[test]
+    fn narrow() {
+        assert_eq!(
+            channel_plan(12_500, 20_000_000),
+            Some(ChannelPlan {
+                sample_rate: 2_000_000,
+                baseband_filter_bw: 1_750_000,
+                decimation: 80,
+                channel_rate: 25_000,
+            })
+        );
+    }
+
+    #[test]
+    fn uneven() {
+        // 2 MHz is not a multiple of 2 * 300 kHz
+        let plan: ChannelPlan = channel_plan(300_000, 20_000_000).unwrap();
+        assert_eq!(plan.sample_rate, 2_400_000);
+        assert_eq!(plan.decimation, 4);
+    }
+
+    #[test]
+    fn wide() {
+        assert_eq!(
+            channel_plan(5_000_000, 20_000_000),
+            Some(ChannelPlan {
+                sample_rate: 10_000_000,
+                baseband_filter_bw: 5_000_000,
+                decimation: 1,
+                channel_rate: 10_000_000,
+            })
+        );
+    }
+
+    #[test]
+    fn too_wide() {
+        assert_eq!(channel_plan(10_000_001, 20_000_000), None);
+        assert_eq!(channel_plan(0, 20_000_000), None);
+    }
+}
+
+// Helper for set_sample_rate
+fn baseband_filter_bw_round_down(hz: u32) -> u32 {
+    BASEBAND_FILTER_BW
+        .iter()
+        .copied()
+        .rev()
+        .find(|&bw| bw <= hz)
+        .unwrap_or(BASEBAND_FILTER_BW[0])
+}
+
+#[cfg(test)]
+mod baseband_filter_bw {
+    use super::{baseband_filter_bw_round_down, baseband_filter_bw_round_up};
+
+    #[test]
+    fn round_up() {
+        assert_eq!(baseband_filter_bw_round_up(1_750_000), Some(1_750_000));
+        assert_eq!(baseband_filter_bw_round_up(10_500_000), Some(12_000_000));
+        assert_eq!(baseband_filter_bw_round_up(28_000_000), Some(28_000_000));
+        assert_eq!(baseband_filter_bw_round_up(28_000_001), None);
+    }
+
+    #[test]
+    fn round_down() {
+        assert_eq!(baseband_filter_bw_round_down(0), 1_750_000);
+        assert_eq!(baseband_filter_bw_round_down(7_500_000), 7_000_000);
+        assert_eq!(baseband_filter_bw_round_down(15_000_000), 15_000_000);
+        assert_eq!(baseband_filter_bw_round_down(40_000_000), 28_000_000);
+    }
+}
+
+const IF_MIN_HZ: u64 = 2_150_000_000;
+const IF_MAX_HZ: u64 = 2_750_000_000;
+const LO_MIN_HZ: u64 = 84_375_000;
+const LO_MAX_HZ: u64 = 5_400_000_000;
+
+// Helper for set_freq_explicit, RF frequency of a valid IF and LO pair
+fn explicit_rf_freq(if_hz: u64, lo_hz: u64, path: RfPath) -> Option<u64> {
+    if !(IF_MIN_HZ..=IF_MAX_HZ).contains(&if_hz) {
+        return None;
+    }
+    if path != RfPath::Bypass && !(LO_MIN_HZ..=LO_MAX_HZ).contains(&lo_hz) {
+        return None;
+    }
+    match path {
+        RfPath::Bypass => Some(if_hz),
+        RfPath::LowPass => lo_hz.checked_sub(if_hz).filter(|&hz| hz > 0),
+        RfPath::HighPass => Some(if_hz + lo_hz),
+    }
+}
+
+// Helper for set_rf_path, IF and LO for an RF frequency on a forced path
+fn forced_path_plan(rf_hz: u64, path: RfPath) -> (u64, u64) {
+    match path {
+        RfPath::Bypass => (rf_hz, 0),
+        RfPath::LowPass => (2_650_000_000, 2_650_000_000 + rf_hz),
+        RfPath::HighPass => (IF_MIN_HZ, rf_hz.saturating_sub(IF_MIN_HZ)),
+    }
+}
+
+// Helper for set_freq_explicit
+fn freq_explicit_params(if_hz: u64, lo_hz: u64, path: RfPath) -> [u8; 17] {
+    let mut buf: [u8; 17] = [0; 17];
+    buf[0..8].copy_from_slice(&if_hz.to_le_bytes());
+    buf[8..16].copy_from_slice(&lo_hz.to_le_bytes());
+    buf[16] = path as u8;
+    buf
+}
+
+#[cfg(test)]
+mod freq_explicit {
+    use super::{explicit_rf_freq, forced_path_plan, freq_explicit_params, RfPath};
+
+    #[test]
+    fn params() {
+        assert_eq!(
+            freq_explicit_params(2_600_000_000, 2_700_000_000, RfPath::LowPass),
+            [
+                0x00, 0xDA, 0xF8, 0x9A, 0x00, 0x00, 0x00, 0x00, // IF
+                0x00, 0xBB, 0xEE, 0xA0, 0x00, 0x00, 0x00, 0x00, // LO
+                0x01, // path
+            ]
+        );
+    }
+
+    #[test]
+    fn rf_freq() {
+        const IF: u64 = 2_600_000_000;
+        assert_eq!(
+            explicit_rf_freq(IF, 2_700_000_000, RfPath::LowPass),
+            Some(100_000_000)
+        );
+        assert_eq!(
+            explicit_rf_freq(IF, 2_700_000_000, RfPath::HighPass),
+            Some(5_300_000_000)
+        );
+        assert_eq!(explicit_rf_freq(IF, 0, RfPath::Bypass), Some(IF));
+        assert_eq!(explicit_rf_freq(IF, 0, RfPath::LowPass), None);
+        assert_eq!(explicit_rf_freq(IF, IF, RfPath::LowPass), None);
+        assert_eq!(explicit_rf_freq(3_000_000_000, 0, RfPath::Bypass), None);
+    }
+
+    #[test]
+    fn forced_round_trip() {
+        for (rf, path) in [
+            (100_000_000, RfPath::LowPass),
+            (2_450_000_000, RfPath::LowPass),
+            (2_450_000_000, RfPath::Bypass),
+            (2_450_000_000, RfPath::HighPass),
+            (5_800_000_000, RfPath::HighPass),
+        ] {
+            let (if_hz, lo_hz) = forced_path_plan(rf, path);
+            assert_eq!(explicit_rf_freq(if_hz, lo_hz, path), Some(rf));
+        }
+    }
+}
+
+/// Length of the default preamble of tx_with_preamble in samples.
+const TX_RAMP_SAMPLES: usize = 256;
+
+// Helper for tx_with_preamble, raised cosine ramp up to the first sample
+fn ramp_preamble(payload: &[u8], num_samples: usize) -> Vec<u8> {
+    let (i, q): (f64, f64) = match payload {
+        [i, q, ..] => (f64::from(*i as i8), f64::from(*q as i8)),
+        _ => (0.0, 0.0),
+    };
+    (0..num_samples)
+        .flat_map(|n| {
+            let x: f64 = std::f64::consts::PI * (n as f64) / (num_samples as f64);
+            let scale: f64 = 0.5 - 0.5 * x.cos();
+            [
+                (i * scale).round() as i8 as u8,
+                (q * scale).round() as i8 as u8,
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod ramp_preamble {
+    use super::ramp_preamble;
+
+    #[test]
+    fn ramp() {
+        assert_eq!(
+            ramp_preamble(&[100, 0x9C, 1, 1], 4),
+            [0, 0, 15, 241, 50, 206, 85, 171]
+        );
+    }
+
+    #[test]
+    fn empty_payload() {
+        assert_eq!(ramp_preamble(&[], 2), [0; 4]);
+    }
+}
+
+/// Maximum number of Opera Cake boards.
+const OPERACAKE_MAX_BOARDS: usize = 8;
+
+// Helper for operacake_boards, the list is terminated by an invalid address
+fn operacake_addresses(buf: &[u8]) -> Vec<u8> {
+    const ADDRESS_INVALID: u8 = 0xFF;
+    buf.iter()
+        .copied()
+        .take_while(|&addr| addr != ADDRESS_INVALID)
+        .collect()
+}
+
+#[cfg(test)]
+mod operacake_addresses {
+    use super::operacake_addresses;
+
+    #[test]
+    fn none() {
+        assert!(operacake_addresses(&[0xFF; 8]).is_empty());
+    }
+
+    #[test]
+    fn some() {
+        assert_eq!(
+            operacake_addresses(&[0, 2, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]),
+            [0, 2]
+        );
+    }
+
+    #[test]
+    fn full() {
+        assert_eq!(
+            operacake_addresses(&[0, 1, 2, 3, 4, 5, 6, 7]),
+            [0, 1, 2, 3, 4, 5, 6, 7]
+        );
+    }
+}
+
+// Helper for transmit_file, reads until `buf` is full or the end of input
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut n: usize = 0;
+    while n < buf.len() {
+        match reader.read(&mut buf[n..]) {
+            Ok(0) => break,
+            Ok(len) => n += len,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(n)
+}
+
+#[cfg(test)]
+mod read_full {
+    use super::read_full;
+    use std::io::Read;
+
+    #[test]
+    fn full() {
+        let mut buf: [u8; 4] = [0; 4];
+        assert_eq!(
+            read_full(&mut [1, 2, 3, 4, 5].as_slice(), &mut buf).unwrap(),
+            4
+        );
+        assert_eq!(buf, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn short_reads() {
+        let mut reader = [1, 2]
+            .as_slice()
+            .chain([3].as_slice())
+            .chain([4, 5].as_slice());
+        let mut buf: [u8; 4] = [0; 4];
+        assert_eq!(read_full(&mut reader, &mut buf).unwrap(), 4);
+        assert_eq!(buf, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn end_of_input() {
+        let mut buf: [u8; 4] = [0; 4];
+        assert_eq!(read_full(&mut [1, 2, 3].as_slice(), &mut buf).unwrap(), 3);
+        assert_eq!(read_full(&mut [].as_slice(), &mut buf).unwrap(), 0);
+    }
+}
+
+// Helper for set_operacake_ports, the ports must be on opposite sides
+fn operacake_ports_index(port_a: OperacakePort, port_b: OperacakePort) -> Option<u16> {
+    if port_a.is_a() == port_b.is_a() {
+        None
+    } else {
+        Some(u16::from(port_a as u8) | (u16::from(port_b as u8) << 8))
+    }
+}
+
+#[cfg(test)]
+mod operacake_ports_index {
+    use super::{operacake_ports_index, OperacakePort};
+
+    #[test]
+    fn nominal() {
+        assert_eq!(
+            operacake_ports_index(OperacakePort::A2, OperacakePort::B1),
+            Some(0x0401)
+        );
+        assert_eq!(
+            operacake_ports_index(OperacakePort::B4, OperacakePort::A1),
+            Some(0x0007)
+        );
+    }
+
+    #[test]
+    fn same_side() {
+        assert_eq!(
+            operacake_ports_index(OperacakePort::A1, OperacakePort::A2),
+            None
+        );
+        assert_eq!(
+            operacake_ports_index(OperacakePort::B3, OperacakePort::B3),
+            None
+        );
+    }
+}
+
+// Helper for read_status, shortfalls since the count recorded by clear_status
+fn shortfalls_since(count: u32, baseline: Option<u32>) -> u32 {
+    match baseline {
+        // a smaller count means the firmware reset the counters
+        Some(baseline) => count.checked_sub(baseline).unwrap_or(count),
+        None => count,
+    }
+}
+
+#[cfg(test)]
+mod shortfalls_since {
+    use super::shortfalls_since;
+
+    #[test]
+    fn no_baseline() {
+        assert_eq!(shortfalls_since(0, None), 0);
+        assert_eq!(shortfalls_since(7, None), 7);
+    }
+
+    #[test]
+    fn since_baseline() {
+        assert_eq!(shortfalls_since(7, Some(7)), 0);
+        assert_eq!(shortfalls_since(10, Some(7)), 3);
+    }
+
+    #[test]
+    fn counters_reset() {
+        assert_eq!(shortfalls_since(2, Some(7)), 2);
+    }
+}
+
+/// Step of a reset, in the order they are sent.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ResetStep {
+    /// Turn the transceiver off.
+    Off,
+    /// Clear the halt on the streaming endpoint.
+    ClearHalt,
+    /// Release the streaming interface.
+    Release,
+    /// Request the reset.
+    Reset,
+}
+
+// Helper for reset, the steps before the reset are best-effort
+fn reset_sequence(mut step: impl FnMut(ResetStep) -> Result<(), Error>) -> Result<(), Error> {
+    for best_effort in [ResetStep::Off, ResetStep::ClearHalt, ResetStep::Release] {
+        step(best_effort).ok();
+    }
+    step(ResetStep::Reset)
+}
+
+#[cfg(test)]
+mod reset_sequence {
+    use super::{reset_sequence, Error, ResetStep};
+
+    fn run(fail: &[ResetStep]) -> (Vec<ResetStep>, Result<(), Error>) {
+        let mut steps: Vec<ResetStep> = Vec::new();
+        let result: Result<(), Error> = reset_sequence(|step| {
+            steps.push(step);
+            if fail.contains(&step) {
+                Err(Error::Usb(rusb::Error::Pipe))
+            } else {
+                Ok(())
+            }
+        });
+        (steps, result)
+    }
+
+    #[test]
+    fn off_then_reset() {
+        let (steps, result) = run(&[]);
+        assert_eq!(
+            steps,
+            [
+                ResetStep::Off,
+                ResetStep::ClearHalt,
+                ResetStep::Release,
+                ResetStep::Reset
+            ]
+        );
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn off_fails() {
+        let (steps, result) = run(&[ResetStep::Off]);
+        assert_eq!(steps.last(), Some(&ResetStep::Reset));
+        assert_eq!(steps.len(), 4);
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn wedged() {
+        // a wedged device fails every request before the reset
+        let (steps, result) = run(&[ResetStep::Off, ResetStep::ClearHalt, ResetStep::Release]);
+        assert_eq!(steps.last(), Some(&ResetStep::Reset));
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn reset_fails() {
+        let (_, result) = run(&[ResetStep::Reset]);
+        assert_eq!(result, Err(Error::Usb(rusb::Error::Pipe)));
+    }
+}
+
+// Helper for set_timeout, libusb truncates to whole milliseconds
+fn valid_timeout(duration: Duration) -> bool {
+    duration.as_millis() != 0
+}
+
+#[cfg(test)]
+mod valid_timeout {
+    use super::valid_timeout;
+    use std::time::Duration;
+
+    #[test]
+    fn zero() {
+        assert!(!valid_timeout(Duration::ZERO));
+    }
+
+    #[test]
+    fn sub_millisecond() {
+        assert!(!valid_timeout(Duration::from_nanos(1)));
+        assert!(!valid_timeout(Duration::from_micros(999)));
+    }
+
+    #[test]
+    fn millisecond() {
+        assert!(valid_timeout(Duration::from_millis(1)));
+        assert!(valid_timeout(Duration::from_micros(1500)));
+        assert!(valid_timeout(Duration::from_secs(1)));
+    }
+}
+
+// Helper for set_low_latency, saves the timeout it replaces and restores it
+fn low_latency_timeout(enable: bool, to: &mut Duration, saved_to: &mut Option<Duration>) {
+    if enable {
+        if saved_to.is_none() {
+            *saved_to = Some(*to);
+        }
+        *to = TIMEOUT_LOW_LATENCY;
+    } else if let Some(prev) = saved_to.take() {
+        *to = prev;
+    }
+}
+
+#[cfg(test)]
+mod low_latency_timeout {
+    use super::{low_latency_timeout, TIMEOUT_DEFAULT, TIMEOUT_LOW_LATENCY};
+    use std::time::Duration;
+
+    #[test]
+    fn restores_default() {
+        let mut to: Duration = TIMEOUT_DEFAULT;
+        let mut saved_to: Option<Duration> = None;
+        low_latency_timeout(true, &mut to, &mut saved_to);
+        assert_eq!(to, TIMEOUT_LOW_LATENCY);
+        low_latency_timeout(false, &mut to, &mut saved_to);
+        assert_eq!(to, TIMEOUT_DEFAULT);
+        assert_eq!(saved_to, None);
+    }
+
+    #[test]
+    fn restores_user_timeout() {
+        let user: Duration = Duration::from_secs(5);
+        let mut to: Duration = user;
+        let mut saved_to: Option<Duration> = None;
+        low_latency_timeout(true, &mut to, &mut saved_to);
+        low_latency_timeout(true, &mut to, &mut saved_to);
+        assert_eq!(to, TIMEOUT_LOW_LATENCY);
+        low_latency_timeout(false, &mut to, &mut saved_to);
+        assert_eq!(to, user);
+    }
+
+    #[test]
+    fn disable_without_enable() {
+        let user: Duration = Duration::from_secs(5);
+        let mut to: Duration = user;
+        let mut saved_to: Option<Duration> = None;
+        low_latency_timeout(false, &mut to, &mut saved_to);
+        assert_eq!(to, user);
+    }
+}
+
+// Helper for tx_padded, length rounded up to a whole USB packet
+fn padded_len(len: usize) -> usize {
+    len.div_ceil(USB_PACKET_SIZE) * USB_PACKET_SIZE
+}
+
+#[cfg(test)]
+mod padded_len {
+    use super::padded_len;
+
+    #[test]
+    fn aligned() {
+        assert_eq!(padded_len(0), 0);
+        assert_eq!(padded_len(512), 512);
+        assert_eq!(padded_len(128 * 1024), 128 * 1024);
+    }
+
+    #[test]
+    fn partial() {
+        assert_eq!(padded_len(1), 512);
+        assert_eq!(padded_len(511), 512);
+        assert_eq!(padded_len(513), 1024);
+    }
+}
+
+// Helper for set_freq
+fn corrected_freq(hz: u64, ppm: f32) -> u64 {
+    (hz as f64 * (1.0 + f64::from(ppm) / 1e6)).round() as u64
+}
+
+#[cfg(test)]
+mod corrected_freq {
+    use super::corrected_freq;
+
+    #[test]
+    fn zero() {
+        assert_eq!(corrected_freq(915_000_000, 0.0), 915_000_000);
+    }
+
+    #[test]
+    fn positive() {
+        assert_eq!(corrected_freq(1_000_000_000, 20.0), 1_000_020_000);
+    }
+
+    #[test]
+    fn negative() {
+        assert_eq!(corrected_freq(915_000_000, -2.0), 914_998_170);
+    }
+}
+
+// Helper for set_freq
+fn freq_params(hz: u64) -> [u8; 8] {
+    const MHZ: u64 = 1_000_000;
+
+    let l_freq_mhz: u32 = u32::try_from(hz / MHZ).unwrap_or(u32::MAX).to_le();
+    let l_freq_hz: u32 = u32::try_from(hz - u64::from(l_freq_mhz) * MHZ)
+        .unwrap_or(u32::MAX)
+        .to_le();
+
+    [
+        (l_freq_mhz & 0xFF) as u8,
+        ((l_freq_mhz >> 8) & 0xFF) as u8,
+        ((l_freq_mhz >> 16) & 0xFF) as u8,
+        ((l_freq_mhz >> 24) & 0xFF) as u8,
+        (l_freq_hz & 0xFF) as u8,
+        ((l_freq_hz >> 8) & 0xFF) as u8,
+        ((l_freq_hz >> 16) & 0xFF) as u8,
+        ((l_freq_hz >> 24) & 0xFF) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod freq_params {
+    use super::freq_params;
+
+    #[test]
+    fn nominal() {
+        assert_eq!(freq_params(915_000_000), [0x93, 0x03, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(freq_params(915_000_001), [0x93, 0x03, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(
+            freq_params(123456789),
+            [0x7B, 0, 0, 0, 0x55, 0xF8, 0x06, 0x00]
+        );
+    }
+
+    #[test]
+    fn min() {
+        assert_eq!(freq_params(0), [0; 8]);
+    }
+
+    #[test]
+    fn max() {
+        assert_eq!(freq_params(u64::MAX), [0xFF; 8]);
+    }
+}
+
+/// Round a frequency to the nearest frequency the synthesizer can tune to
+/// on the bypass RF path.
+///
+/// The MAX2837 fractional-N synthesizer has a 20-bit fractional divider
+/// with a 30 MHz comparison frequency, a step of 30 MHz / 2<sup>20</sup>,
+/// about 28.61 Hz.
+/// The firmware does not round a requested frequency to the nearest step,
+/// it computes the divider by successive halving of the comparison
+/// frequency, which truncates to a step at or below the request.
+/// This returns the smallest whole Hz frequency the firmware tunes to the
+/// step nearest `hz`, so tuning to the result reaches that step, and the
+/// synthesizer frequency is within 1 Hz of the result.
+///
+/// Only the bypass path is modelled, where the RF frequency is the MAX2837
+/// frequency; [`set_freq`] selects it from 2.17 GHz to 2.74 GHz, and
+/// [`set_rf_path`] can force it over the whole 2.15 GHz to 2.75 GHz
+/// range of the MAX2837.
+/// Other frequencies are mixed with the RFFC5071 LO and depend on the IF and
+/// LO plan of the firmware, which is not modelled; `hz` is clamped to the
+/// 2.15 GHz to 2.75 GHz range, and the result is always within it.
+/// Rounding the result again returns the same value.
+///
+/// # Example
+///
+/// ```
+/// use hackrfone::nearest_tunable_freq;
+///
+/// assert_eq!(nearest_tunable_freq(2_400_000_000), 2_400_000_000);
+/// assert_eq!(nearest_tunable_freq(2_437_000_000), 2_437_000_007);
+/// ```
+///
+/// [`set_freq`]: crate::HackRfOne::set_freq
+/// [`set_rf_path`]: crate::HackRfOne::set_rf_path
+pub fn nearest_tunable_freq(hz: u64) -> u64 {
+    let hz: u64 = hz.clamp(IF_MIN_HZ, IF_MAX_HZ);
+    let mut steps: u64 = ((hz << MAX2837_FRAC_BITS) + MAX2837_REF_HZ / 2) / MAX2837_REF_HZ;
+    while max2837_request(steps) < IF_MIN_HZ {
+        steps += 1;
+    }
+    while max2837_request(steps) > IF_MAX_HZ {
+        steps -= 1;
+    }
+    max2837_request(steps)
+}
+
+/// MAX2837 synthesizer comparison frequency in Hz.
+const MAX2837_REF_HZ: u64 = 30_000_000;
+
+/// Bits of the MAX2837 fractional divider.
+const MAX2837_FRAC_BITS: u32 = 20;
+
+// Helper for nearest_tunable_freq, the synthesizer steps the firmware tunes
+// to for a frequency, as computed by max2837_set_frequency
+fn max2837_steps(hz: u64) -> u64 {
+    let mut rem: u64 = hz % MAX2837_REF_HZ;
+    let mut cmp: u64 = MAX2837_REF_HZ;
+    let mut frac: u64 = 0;
+    for _ in 0..MAX2837_FRAC_BITS {
+        frac <<= 1;
+        cmp >>= 1;
+        if rem > cmp {
+            frac |= 1;
+            rem -= cmp;
+        }
+    }
+    ((hz / MAX2837_REF_HZ) << MAX2837_FRAC_BITS) | frac
+}
+
+// Helper for nearest_tunable_freq, the smallest frequency the firmware tunes
+// to `steps` for
+fn max2837_request(steps: u64) -> u64 {
+    let mut hz: u64 = (steps * MAX2837_REF_HZ) >> MAX2837_FRAC_BITS;
+    while max2837_steps(hz) < steps {
+        hz += 1;
+    }
+    hz
+}
+
+#[cfg(test)]
+mod nearest_tunable_freq {
+    use super::{max2837_steps, nearest_tunable_freq, IF_MAX_HZ, IF_MIN_HZ};
+
+    #[test]
+    fn firmware_truncates() {
+        assert_eq!(max2837_steps(2_400_000_000), 80 << 20);
+        assert_eq!(max2837_steps(2_400_000_028), 80 << 20);
+        assert_eq!(max2837_steps(2_400_000_029), (80 << 20) + 1);
+    }
+
+    #[test]
+    fn on_grid() {
+        assert_eq!(nearest_tunable_freq(2_400_000_000), 2_400_000_000);
+        assert_eq!(nearest_tunable_freq(2_430_000_000), 2_430_000_000);
+    }
+
+    #[test]
+    fn band_edges() {
+        assert_eq!(nearest_tunable_freq(2_150_000_000), 2_150_000_009);
+        assert_eq!(nearest_tunable_freq(2_750_000_000), 2_749_999_980);
+    }
+
+    #[test]
+    fn out_of_band() {
+        assert_eq!(nearest_tunable_freq(0), 2_150_000_009);
+        assert_eq!(nearest_tunable_freq(1_000_000), 2_150_000_009);
+        assert_eq!(nearest_tunable_freq(u64::MAX), 2_749_999_980);
+    }
+
+    #[test]
+    fn rounding() {
+        assert_eq!(nearest_tunable_freq(2_400_000_014), 2_400_000_000);
+        assert_eq!(nearest_tunable_freq(2_400_000_015), 2_400_000_029);
+    }
+
+    #[test]
+    fn reaches_nearest_step() {
+        for hz in [2_150_000_123, 2_437_000_000, 2_450_000_001, 2_749_999_000] {
+            let tuned: u64 = nearest_tunable_freq(hz);
+            let steps: u64 = max2837_steps(tuned);
+            let nearest: u64 = ((hz << 20) + 15_000_000) / 30_000_000;
+            assert_eq!(steps, nearest);
+            assert!((IF_MIN_HZ..=IF_MAX_HZ).contains(&tuned));
+        }
+    }
+
+    #[test]
+    fn idempotent() {
+        for hz in [
+            0,
+            2_150_000_000,
+            2_437_000_000,
+            2_450_000_001,
+            2_750_000_000,
+        ] {
+            let tuned: u64 = nearest_tunable_freq(hz);
+            assert_eq!(nearest_tunable_freq(tuned), tuned);
+        }
+    }
+}
+
+// Helper for set_sample_rate and validate_scan
+fn valid_sample_rate(hz: u32, max: u32) -> bool {
+    hz != 0 && hz <= max
+}
+
+#[cfg(test)]
+mod valid_sample_rate {
+    use super::{valid_sample_rate, SAMPLE_RATE_MAX_HZ};
+
+    #[test]
+    fn zero() {
+        assert!(!valid_sample_rate(0, SAMPLE_RATE_MAX_HZ));
+    }
+
+    #[test]
+    fn below_recommended() {
+        assert!(valid_sample_rate(1, SAMPLE_RATE_MAX_HZ));
+        assert!(valid_sample_rate(1_999_999, SAMPLE_RATE_MAX_HZ));
+    }
+
+    #[test]
+    fn max() {
+        assert!(valid_sample_rate(SAMPLE_RATE_MAX_HZ, SAMPLE_RATE_MAX_HZ));
+        assert!(!valid_sample_rate(
+            SAMPLE_RATE_MAX_HZ + 1,
+            SAMPLE_RATE_MAX_HZ
+        ));
+    }
+}
+
+/// Check a scan plan without touching the hardware.
+///
+/// Every frequency must be within [`FREQ_MIN_HZ`] and [`FREQ_MAX_HZ`], and
+/// the sample rate must be accepted by [`set_sample_rate`] on a HackRF One,
+/// non-zero and no more than [`SAMPLE_RATE_MAX_HZ`].
+/// Use this to catch configuration mistakes before starting a long scan.
+///
+/// # Errors
+///
+/// Returns [`Error::ScanFrequency`] with the index of the first frequency
+/// out of range, or [`Error::ScanSampleRate`] if the sample rate is out of
+/// range.
+/// Frequencies are checked before the sample rate.
+///
+/// # Example
+///
+/// ```
+/// use hackrfone::{validate_scan, Error};
+///
+/// let freqs: Vec<u64> = (0..10).map(|n| 2_400_000_000 + n * 10_000_000).collect();
+/// assert_eq!(validate_scan(&freqs, 10_000_000), Ok(()));
+/// assert_eq!(
+///     validate_scan(&[915_000_000, 7_000_000_000], 10_000_000),
+///     Err(Error::ScanFrequency {
+///         index: 1,
+///         hz: 7_000_000_000
+///     })
+/// );
+/// ```
+///
+/// [`set_sample_rate`]: crate::HackRfOne::set_sample_rate
+pub fn validate_scan(freqs: &[u64], sample_rate_hz: u32) -> Result<(), Error> {
+    if let Some((index, &hz)) = freqs
+        .iter()
+        .enumerate()
+        .find(|(_, hz)| !(FREQ_MIN_HZ..=FREQ_MAX_HZ).contains(hz))
+    {
+        Err(Error::ScanFrequency { index, hz })
+    } else if !valid_sample_rate(sample_rate_hz, SAMPLE_RATE_MAX_HZ) {
+        Err(Error::ScanSampleRate { hz: sample_rate_hz })
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod validate_scan {
+    use super::{validate_scan, Error};
+
+    #[test]
+    fn nominal() {
+        assert_eq!(
+            validate_scan(&[1_000_000, 915_000_000, 6_000_000_000], 20_000_000),
+            Ok(())
+        );
+        assert_eq!(validate_scan(&[], 2_000_000), Ok(()));
+        assert_eq!(validate_scan(&[915_000_000], 1_000_000), Ok(()));
+    }
+
+    #[test]
+    fn freq_out_of_range() {
+        assert_eq!(
+            validate_scan(&[999_999], 10_000_000),
+            Err(Error::ScanFrequency {
+                index: 0,
+                hz: 999_999
+            })
+        );
+        assert_eq!(
+            validate_scan(&[915_000_000, 6_000_000_001, 0], 10_000_000),
+            Err(Error::ScanFrequency {
+                index: 1,
+                hz: 6_000_000_001
+            })
+        );
+    }
+
+    #[test]
+    fn sample_rate_out_of_range() {
+        assert_eq!(
+            validate_scan(&[915_000_000], 0),
+            Err(Error::ScanSampleRate { hz: 0 })
+        );
+        assert_eq!(
+            validate_scan(&[915_000_000], 20_000_001),
+            Err(Error::ScanSampleRate { hz: 20_000_001 })
+        );
+    }
+
+    #[test]
+    fn freq_before_sample_rate() {
+        assert_eq!(
+            validate_scan(&[0], 0),
+            Err(Error::ScanFrequency { index: 0, hz: 0 })
+        );
+    }
+}
+
+/// Convert an IQ sample pair to a complex number.
+///
+/// # Example
+///
+/// Post-processing sample data.
+///
+/// ```no_run
+/// use hackrfone::{iq_to_cplx_i8, HackRfOne, RxMode, UnknownMode};
+///
+/// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+/// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+/// let data: Vec<u8> = radio.rx()?;
+/// radio.stop_rx()?;
+///
+/// for iq in data.chunks_exact(2) {
+///     let cplx: num_complex::Complex<i8> = iq_to_cplx_i8(iq[0], iq[1]);
+///     // .. do whatever you want with cplx here
+/// }
+///
+/// # Ok::<(), hackrfone::Error>(())
+/// ```
+///
+/// Guide level explanation.
+///
+/// ```
+/// use hackrfone::iq_to_cplx_i8;
+/// use num_complex::Complex;
+///
+/// assert_eq!(iq_to_cplx_i8(255, 1), Complex::new(-1, 1));
+/// ```
+#[cfg(feature = "num-complex")]
+pub fn iq_to_cplx_i8(i: u8, q: u8) -> num_complex::Complex<i8> {
+    num_complex::Complex::new(i as i8, q as i8)
+}
+
+/// Convert an IQ sample pair to a floating point complex number.
+///
+/// Generally you will want to use [`iq_to_cplx_i8`] for storing or transfering
+/// data because the samples are 2-bytes in the native i8, vs 8-bytes in f32.
+///
+/// Floats are easier to work with for running samples through digital signal
+/// processing algorithms (e.g. discrete fourier transform) where the i8 can
+/// easily saturate.
+///
+/// # Example
+///
+/// Post-processing sample data.
+///
+/// ```no_run
+/// use hackrfone::{iq_to_cplx_f32, HackRfOne, RxMode, UnknownMode};
+///
+/// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+/// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+/// let data: Vec<u8> = radio.rx()?;
+/// radio.stop_rx()?;
+///
+/// for iq in data.chunks_exact(2) {
+///     let cplx: num_complex::Complex<f32> = iq_to_cplx_f32(iq[0], iq[1]);
+///     // .. do whatever you want with cplx here
+/// }
+///
+/// # Ok::<(), hackrfone::Error>(())
+/// ```
+///
+/// Guide level explanation.
+///
+/// ```
+/// use hackrfone::iq_to_cplx_f32;
+/// use num_complex::Complex;
+///
+/// assert_eq!(iq_to_cplx_f32(255, 1), Complex::new(-1.0, 1.0));
+/// ```
+#[cfg(feature = "num-complex")]
+pub fn iq_to_cplx_f32(i: u8, q: u8) -> num_complex::Complex<f32> {
+    num_complex::Complex::new(i as i8 as f32, q as i8 as f32)
+}
+
+/// Convert an IQ sample pair to a 16-bit complex number.
+///
+/// This has more headroom than [`iq_to_cplx_i8`] for integer processing,
+/// e.g. summing samples in a resampler.
+///
+/// # Example
+///
+/// ```
+/// use hackrfone::iq_to_cplx_i16;
+/// use num_complex::Complex;
+///
+/// assert_eq!(iq_to_cplx_i16(255, 1), Complex::new(-1, 1));
+/// ```
+#[cfg(feature = "num-complex")]
+pub fn iq_to_cplx_i16(i: u8, q: u8) -> num_complex::Complex<i16> {
+    num_complex::Complex::new(i16::from(i as i8), i16::from(q as i8))
+}
+
+/// Convert an IQ sample pair to a double precision complex number.
+///
+/// # Example
+///
+/// ```
+/// use hackrfone::iq_to_cplx_f64;
+/// use num_complex::Complex;
+///
+/// assert_eq!(iq_to_cplx_f64(255, 1), Complex::new(-1.0, 1.0));
+/// ```
+#[cfg(feature = "num-complex")]
+pub fn iq_to_cplx_f64(i: u8, q: u8) -> num_complex::Complex<f64> {
+    num_complex::Complex::new(f64::from(i as i8), f64::from(q as i8))
+}
+
+/// Convert a buffer of IQ samples to complex numbers with [`iq_to_cplx_i8`].
+///
+/// A trailing odd byte is ignored.
+///
+/// # Example
+///
+/// ```
+/// use hackrfone::samples_to_cplx_i8;
+/// use num_complex::Complex;
+///
+/// assert_eq!(samples_to_cplx_i8(&[255, 1, 2]), [Complex::new(-1, 1)]);
+/// ```
+#[cfg(feature = "num-complex")]
+pub fn samples_to_cplx_i8(buf: &[u8]) -> Vec<num_complex::Complex<i8>> {
+    buf.chunks_exact(2)
+        .map(|iq| iq_to_cplx_i8(iq[0], iq[1]))
+        .collect()
+}
+
+/// Convert a buffer of IQ samples to complex numbers with [`iq_to_cplx_i16`].
+///
+/// A trailing odd byte is ignored.
+///
+/// # Example
+///
+/// ```
+/// use hackrfone::samples_to_cplx_i16;
+/// use num_complex::Complex;
+///
+/// assert_eq!(samples_to_cplx_i16(&[255, 1, 2]), [Complex::new(-1, 1)]);
+/// ```
+#[cfg(feature = "num-complex")]
+pub fn samples_to_cplx_i16(buf: &[u8]) -> Vec<num_complex::Complex<i16>> {
+    buf.chunks_exact(2)
+        .map(|iq| iq_to_cplx_i16(iq[0], iq[1]))
+        .collect()
+}
+
+/// Convert a buffer of IQ samples to complex numbers with [`iq_to_cplx_f32`].
+///
+/// A trailing odd byte is ignored.
+///
+/// # Example
+///
+/// ```
+/// use hackrfone::samples_to_cplx_f32;
+/// use num_complex::Complex;
+///
+/// assert_eq!(samples_to_cplx_f32(&[255, 1, 2]), [Complex::new(-1.0, 1.0)]);
+/// ```
+#[cfg(feature = "num-complex")]
+pub fn samples_to_cplx_f32(buf: &[u8]) -> Vec<num_complex::Complex<f32>> {
+    buf.chunks_exact(2)
+        .map(|iq| iq_to_cplx_f32(iq[0], iq[1]))
+        .collect()
+}
+
+/// Convert a buffer of IQ samples to complex numbers with [`iq_to_cplx_f32`]
+/// into a preallocated slice.
+///
+/// Together with [`rx_into`] this allows receiving and converting samples
+/// without allocating.
+///
+/// Conversion stops when either `buf` or `out` is exhausted, a trailing odd
+/// byte in `buf` is ignored.
+///
+/// Returns the number of samples written to `out`.
+///
+/// # Example
+///
+/// ```
+/// use hackrfone::samples_to_cplx_f32_into;
+/// use num_complex::Complex;
+///
+/// let mut out: [Complex<f32>; 4] = [Complex::new(0.0, 0.0); 4];
+/// assert_eq!(samples_to_cplx_f32_into(&[255, 1, 2, 3], &mut out), 2);
+/// assert_eq!(out[..2], [Complex::new(-1.0, 1.0), Complex::new(2.0, 3.0)]);
+/// ```
+///
+/// [`rx_into`]: crate::HackRfOne::rx_into
+#[cfg(feature = "num-complex")]
+pub fn samples_to_cplx_f32_into(buf: &[u8], out: &mut [num_complex::Complex<f32>]) -> usize {
+    buf.chunks_exact(2)
+        .zip(out.iter_mut())
+        .for_each(|(iq, cplx)| *cplx = iq_to_cplx_f32(iq[0], iq[1]));
+    out.len().min(buf.len() / 2)
+}
+
+/// Correct IQ gain and phase imbalance in place.
+///
+/// Zero-IF receivers have small gain and phase errors between the I and Q
+/// paths, which appear as an image of every signal mirrored around the
+/// center frequency.
+/// The imbalance is modelled as an ideal I path and a Q path with a relative
+/// gain `gain` and phase skew `phase_rad`:
+///
+/// ```text
+/// I' = I
+/// Q' = gain * (Q cos(phase) + I sin(phase))
+/// ```
+///
+/// Each sample is corrected with the inverse of this model:
+///
+/// ```text
+/// I = I'
+/// Q = Q' / (gain cos(phase)) - I' tan(phase)
+/// ```
+///
+/// The coefficients are specific to each device and change little over
+/// time, calibrate them once, for example with a test tone, and apply them
+/// to every block.
+///
+/// # Example
+///
+/// ```
+/// use hackrfone::apply_iq_correction;
+/// use num_complex::Complex;
+///
+/// let mut samples: [Complex<f32>; 1] = [Complex::new(0.0, 2.0)];
+/// apply_iq_correction(&mut samples, 2.0, 0.0);
+/// assert_eq!(samples, [Complex::new(0.0, 1.0)]);
+/// ```
+#[cfg(feature = "num-complex")]
+pub fn apply_iq_correction(samples: &mut [num_complex::Complex<f32>], gain: f32, phase_rad: f32) {
+    let q_scale: f32 = 1.0 / (gain * phase_rad.cos());
+    let i_to_q: f32 = phase_rad.tan();
+    samples
+        .iter_mut()
+        .for_each(|sample| sample.im = sample.im * q_scale - sample.re * i_to_q);
+}
+
+#[cfg(all(test, feature = "num-complex"))]
+mod apply_iq_correction {
+    use super::apply_iq_correction;
+    use num_complex::Complex;
+
+    #[test]
+    fn known_imbalance() {
+        const GAIN: f32 = 1.1;
+        const PHASE: f32 = 0.05;
+
+        let ideal: Vec<Complex<f32>> = (0..64)
+            .map(|n| {
+                let phase: f32 = std::f32::consts::TAU * (n as f32) / 16.0;
+                Complex::new(phase.cos(), phase.sin())
+            })
+            .collect();
+        let mut samples: Vec<Complex<f32>> = ideal
+            .iter()
+            .map(|s| Complex::new(s.re, GAIN * (s.im * PHASE.cos() + s.re * PHASE.sin())))
+            .collect();
+
+        apply_iq_correction(&mut samples, GAIN, PHASE);
+
+        samples.iter().zip(ideal.iter()).for_each(|(s, i)| {
+            assert!((s.re - i.re).abs() < 1e-6);
+            assert!((s.im - i.im).abs() < 1e-6);
+        });
+    }
+}
+
+/// Convert a buffer of IQ samples to complex numbers with [`iq_to_cplx_f64`].
+///
+/// A trailing odd byte is ignored.
+///
+/// # Example
+///
+/// ```
+/// use hackrfone::samples_to_cplx_f64;
+/// use num_complex::Complex;
+///
+/// assert_eq!(samples_to_cplx_f64(&[255, 1, 2]), [Complex::new(-1.0, 1.0)]);
+/// ```
+#[cfg(feature = "num-complex")]
+pub fn samples_to_cplx_f64(buf: &[u8]) -> Vec<num_complex::Complex<f64>> {
+    buf.chunks_exact(2)
+        .map(|iq| iq_to_cplx_f64(iq[0], iq[1]))
+        .collect()
+}
+
+/// Compute a histogram of sample magnitudes.
+///
+/// The magnitude of a sample is `max(|I|, |Q|)`, which ranges from 0 to 128.
+/// This range is split evenly into `bins` bins, the last bin contains samples
+/// at or near full scale.
+///
+/// This is useful for setting gains; samples in the last bin are at risk of
+/// clipping.
+///
+/// A trailing odd byte in `buf` is ignored.
+///
+/// # Example
+///
+/// ```
+/// use hackrfone::amplitude_histogram;
+///
+/// // samples of (0, 0), (-128, 5), and (64, -1)
+/// let buf: [u8; 6] = [0, 0, 0x80, 5, 64, 0xFF];
+/// assert_eq!(amplitude_histogram(&buf, 2), [2, 1]);
+/// ```
+pub fn amplitude_histogram(buf: &[u8], bins: usize) -> Vec<u32> {
+    const NUM_MAGNITUDES: usize = 129;
+
+    let mut histogram: Vec<u32> = vec![0; bins];
+    if bins == 0 {
+        return histogram;
+    }
+
+    buf.chunks_exact(2).for_each(|iq| {
+        let i: usize = usize::from((iq[0] as i8).unsigned_abs());
+        let q: usize = usize::from((iq[1] as i8).unsigned_abs());
+        let bin: usize = i.max(q) * bins / NUM_MAGNITUDES;
+        histogram[bin] = histogram[bin].saturating_add(1);
+    });
+
+    histogram
+}
+
+#[cfg(test)]
+mod amplitude_histogram {
+    use super::amplitude_histogram;
+
+    #[test]
+    fn no_bins() {
+        assert!(amplitude_histogram(&[1, 2, 3, 4], 0).is_empty());
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(amplitude_histogram(&[], 4), [0; 4]);
+    }
+
+    #[test]
+    fn odd_trailing_byte() {
+        assert_eq!(amplitude_histogram(&[0, 0, 0x80], 2), [1, 0]);
+    }
+
+    #[test]
+    fn bin_edges() {
+        // one bin per magnitude
+        let buf: Vec<u8> = (0..=128_i16)
+            .flat_map(|mag| [(-mag) as i8 as u8, 0])
+            .collect();
+        assert_eq!(amplitude_histogram(&buf, 129), [1; 129]);
+    }
+
+    #[test]
+    fn max_of_iq() {
+        assert_eq!(amplitude_histogram(&[1, 127, 0x81, 2], 2), [0, 2]);
+    }
+}
+
+/// Compute the squared magnitude `I² + Q²` of IQ samples into a
+/// preallocated slice.
 ///
-/// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-/// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
-/// let data: Vec<u8> = radio.rx()?;
-/// radio.stop_rx()?;
+/// This works directly on the received bytes without converting to complex
+/// numbers, which makes it cheap enough for energy detection and squelch on
+/// every block.
+/// The largest value is 2 × 128² = 32768, for a sample of (-128, -128).
 ///
-/// for iq in data.chunks_exact(2) {
-///     let cplx: num_complex::Complex<i8> = iq_to_cplx_i8(iq[0], iq[1]);
-///     // .. do whatever you want with cplx here
-/// }
+/// Conversion stops when either `buf` or `out` is exhausted, a trailing odd
+/// byte in `buf` is ignored.
+///
+/// Returns the number of samples written to `out`.
+///
+/// # Example
 ///
-/// # Ok::<(), hackrfone::Error>(())
 /// ```
+/// use hackrfone::magnitude_squared;
 ///
-/// Guide level explanation.
+/// // samples of (3, -4) and (-128, -128)
+/// let buf: [u8; 4] = [3, (-4_i8) as u8, 0x80, 0x80];
+/// let mut out: [u16; 2] = [0; 2];
+/// assert_eq!(magnitude_squared(&buf, &mut out), 2);
+/// assert_eq!(out, [25, 32768]);
+/// ```
+pub fn magnitude_squared(buf: &[u8], out: &mut [u16]) -> usize {
+    buf.chunks_exact(2)
+        .zip(out.iter_mut())
+        .for_each(|(iq, mag)| {
+            let i: u16 = u16::from((iq[0] as i8).unsigned_abs());
+            let q: u16 = u16::from((iq[1] as i8).unsigned_abs());
+            *mag = i * i + q * q;
+        });
+    out.len().min(buf.len() / 2)
+}
+
+#[cfg(test)]
+mod magnitude_squared {
+    use super::magnitude_squared;
+
+    #[test]
+    fn reference() {
+        let buf: Vec<u8> = (0..=255_u8).chain((0..=255_u8).rev()).collect();
+        let mut out: Vec<u16> = vec![0; buf.len() / 2];
+        assert_eq!(magnitude_squared(&buf, &mut out), 256);
+        for (iq, mag) in buf.chunks_exact(2).zip(out) {
+            let i: i32 = i32::from(iq[0] as i8);
+            let q: i32 = i32::from(iq[1] as i8);
+            assert_eq!(i32::from(mag), i * i + q * q);
+        }
+    }
+
+    #[test]
+    fn odd_trailing_byte() {
+        let mut out: [u16; 2] = [0xFFFF; 2];
+        assert_eq!(magnitude_squared(&[1, 2, 3], &mut out), 1);
+        assert_eq!(out, [5, 0xFFFF]);
+    }
+
+    #[test]
+    fn short_output() {
+        let mut out: [u16; 1] = [0; 1];
+        assert_eq!(magnitude_squared(&[1, 2, 3, 4], &mut out), 1);
+        assert_eq!(out, [5]);
+    }
+
+    #[test]
+    fn full_scale() {
+        let mut out: [u16; 1] = [0; 1];
+        magnitude_squared(&[0x80, 0x80], &mut out);
+        assert_eq!(out, [32768]);
+    }
+}
+
+/// Measure the mean power of IQ samples in dBFS.
+///
+/// Full scale is a magnitude of 128, a full scale complex tone measures
+/// about 0 dBFS.
+/// Use [`HackRfOne::power_dbfs`] or [`PortCalibration::power_dbfs`] to
+/// compare power across Opera Cake ports.
+///
+/// A trailing odd byte in `buf` is ignored, an empty buffer measures
+/// negative infinity.
+///
+/// # Example
 ///
 /// ```
-/// use hackrfone::iq_to_cplx_i8;
-/// use num_complex::Complex;
+/// use hackrfone::power_dbfs;
 ///
-/// assert_eq!(iq_to_cplx_i8(255, 1), Complex::new(-1, 1));
+/// // samples of (64, 0) and (0, 64), half of full scale
+/// let dbfs: f32 = power_dbfs(&[64, 0, 0, 64]);
+/// assert!((dbfs - -6.02).abs() < 0.01);
 /// ```
-#[cfg(feature = "num-complex")]
-pub fn iq_to_cplx_i8(i: u8, q: u8) -> num_complex::Complex<i8> {
-    num_complex::Complex::new(i as i8, q as i8)
+pub fn power_dbfs(buf: &[u8]) -> f32 {
+    const FULL_SCALE: f64 = 128.0 * 128.0;
+
+    let num_samples: usize = buf.len() / 2;
+    if num_samples == 0 {
+        return f32::NEG_INFINITY;
+    }
+    let mut mags: [u16; 256] = [0; 256];
+    let sum: u64 = buf
+        .chunks(2 * mags.len())
+        .map(|chunk| {
+            let n: usize = magnitude_squared(chunk, &mut mags);
+            mags[..n].iter().map(|&mag| u64::from(mag)).sum::<u64>()
+        })
+        .sum();
+    let mean: f64 = sum as f64 / num_samples as f64;
+    (10.0 * (mean / FULL_SCALE).log10()) as f32
 }
 
-/// Convert an IQ sample pair to a floating point complex number.
+#[cfg(test)]
+mod power_dbfs {
+    use super::{power_dbfs, OperacakePort, PortCalibration};
+
+    #[test]
+    fn full_scale() {
+        assert_eq!(power_dbfs(&[0x80, 0]), 0.0);
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(power_dbfs(&[]), f32::NEG_INFINITY);
+        assert_eq!(power_dbfs(&[0]), f32::NEG_INFINITY);
+        assert_eq!(power_dbfs(&[0, 0]), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn multiple_chunks() {
+        // more samples than the magnitude buffer, with an odd trailing byte
+        let mut buf: Vec<u8> = [0x80, 0].repeat(300);
+        buf.extend_from_slice(&[0, 0, 0, 0, 0]);
+        assert!((power_dbfs(&buf) - 10.0 * (300.0_f32 / 302.0).log10()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn calibrated() {
+        let mut cal: PortCalibration = PortCalibration::default();
+        cal.set(OperacakePort::B3, -2.0);
+        assert_eq!(cal.power_dbfs(Some(OperacakePort::B3), &[0x80, 0]), -2.0);
+        assert_eq!(cal.power_dbfs(Some(OperacakePort::A1), &[0x80, 0]), 0.0);
+        assert_eq!(cal.power_dbfs(None, &[0x80, 0]), 0.0);
+    }
+}
+
+/// Encoding of 8-bit IQ sample bytes.
 ///
-/// Generally you will want to use [`iq_to_cplx_i8`] for storing or transfering
-/// data because the samples are 2-bytes in the native i8, vs 8-bytes in f32.
+/// The HackRF One sends and receives signed samples, some tools and file
+/// formats use offset binary instead.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum SampleEncoding {
+    /// Two's complement signed, 0 is zero, used by the HackRF One.
+    #[default]
+    Signed,
+    /// Offset binary unsigned, 128 is zero, as used by the RTL-SDR.
+    OffsetBinary,
+}
+
+impl SampleEncoding {
+    /// Convert `buf` in place from this encoding to `to`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hackrfone::SampleEncoding;
+    ///
+    /// let mut buf: [u8; 2] = [0x00, 0xFF];
+    /// SampleEncoding::Signed.convert(SampleEncoding::OffsetBinary, &mut buf);
+    /// assert_eq!(buf, [0x80, 0x7F]);
+    /// ```
+    pub fn convert(self, to: SampleEncoding, buf: &mut [u8]) {
+        if self != to {
+            to_offset_binary(buf)
+        }
+    }
+}
+
+/// Convert signed IQ samples to offset binary in place.
 ///
-/// Floats are easier to work with for running samples through digital signal
-/// processing algorithms (e.g. discrete fourier transform) where the i8 can
-/// easily saturate.
+/// Each byte has 128 added, wrapping, so `-128` maps to `0`, `0` to `128`,
+/// and `127` to `255`.
 ///
 /// # Example
 ///
-/// Post-processing sample data.
+/// ```
+/// use hackrfone::to_offset_binary;
 ///
-/// ```no_run
-/// use hackrfone::{iq_to_cplx_f32, HackRfOne, RxMode, UnknownMode};
+/// let mut buf: [u8; 3] = [(-128_i8) as u8, 0, 127];
+/// to_offset_binary(&mut buf);
+/// assert_eq!(buf, [0, 128, 255]);
+/// ```
+pub fn to_offset_binary(buf: &mut [u8]) {
+    buf.iter_mut().for_each(|b| *b ^= 0x80);
+}
+
+/// Convert offset binary IQ samples to signed in place.
 ///
-/// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
-/// let mut radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
-/// let data: Vec<u8> = radio.rx()?;
-/// radio.stop_rx()?;
+/// This is the inverse of [`to_offset_binary`].
 ///
-/// for iq in data.chunks_exact(2) {
-///     let cplx: num_complex::Complex<f32> = iq_to_cplx_f32(iq[0], iq[1]);
-///     // .. do whatever you want with cplx here
-/// }
+/// # Example
 ///
-/// # Ok::<(), hackrfone::Error>(())
 /// ```
+/// use hackrfone::from_offset_binary;
 ///
-/// Guide level explanation.
+/// let mut buf: [u8; 3] = [0, 128, 255];
+/// from_offset_binary(&mut buf);
+/// assert_eq!(buf, [(-128_i8) as u8, 0, 127]);
+/// ```
+pub fn from_offset_binary(buf: &mut [u8]) {
+    to_offset_binary(buf)
+}
+
+#[cfg(test)]
+mod offset_binary {
+    use super::{from_offset_binary, to_offset_binary, SampleEncoding};
+
+    #[test]
+    fn mapping() {
+        let mut buf: Vec<u8> = (i8::MIN..=i8::MAX).map(|x| x as u8).collect();
+        to_offset_binary(&mut buf);
+        assert_eq!(buf, (0..=255).collect::<Vec<u8>>());
+        from_offset_binary(&mut buf);
+        assert_eq!(
+            buf,
+            (i8::MIN..=i8::MAX).map(|x| x as u8).collect::<Vec<u8>>()
+        );
+    }
+
+    #[test]
+    fn convert_same() {
+        let mut buf: [u8; 2] = [1, 2];
+        SampleEncoding::OffsetBinary.convert(SampleEncoding::OffsetBinary, &mut buf);
+        assert_eq!(buf, [1, 2]);
+        SampleEncoding::OffsetBinary.convert(SampleEncoding::Signed, &mut buf);
+        assert_eq!(buf, [0x81, 0x82]);
+    }
+}
+
+/// Generate a continuous wave (CW) tone.
+///
+/// Returns `num_samples` interleaved signed 8-bit IQ samples of the complex
+/// exponential `amplitude * exp(j * 2π * tone_hz * n / sample_rate_hz)`,
+/// in the same format used for transmitting.
+///
+/// Due to complex upconversion the tone appears at `center_freq + tone_hz`,
+/// a negative `tone_hz` places the tone below the center frequency.
+///
+/// # Example
+///
+/// A tone at a quarter of the sample rate.
 ///
 /// ```
-/// use hackrfone::iq_to_cplx_f32;
-/// use num_complex::Complex;
+/// use hackrfone::generate_tone;
 ///
-/// assert_eq!(iq_to_cplx_f32(255, 1), Complex::new(-1.0, 1.0));
+/// let buf: Vec<u8> = generate_tone(8_000_000, 2_000_000.0, 4, 100);
+/// assert_eq!(buf, [100, 0, 0, 100, 156, 0, 0, 156]);
 /// ```
-#[cfg(feature = "num-complex")]
-pub fn iq_to_cplx_f32(i: u8, q: u8) -> num_complex::Complex<f32> {
-    num_complex::Complex::new(i as i8 as f32, q as i8 as f32)
+pub fn generate_tone(
+    sample_rate_hz: u32,
+    tone_hz: f32,
+    num_samples: usize,
+    amplitude: i8,
+) -> Vec<u8> {
+    let step: f64 = std::f64::consts::TAU * f64::from(tone_hz) / f64::from(sample_rate_hz);
+    let amplitude: f64 = f64::from(amplitude);
+
+    (0..num_samples)
+        .flat_map(|n| {
+            let phase: f64 = step * (n as f64);
+            [
+                (amplitude * phase.cos()).round() as i8 as u8,
+                (amplitude * phase.sin()).round() as i8 as u8,
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod generate_tone {
+    use super::generate_tone;
+
+    #[test]
+    fn dc() {
+        assert_eq!(
+            generate_tone(10_000_000, 0.0, 3, 127),
+            [127, 0, 127, 0, 127, 0]
+        );
+    }
+
+    #[test]
+    fn negative() {
+        assert_eq!(
+            generate_tone(8_000_000, -2_000_000.0, 4, 100),
+            [100, 0, 0, 156, 156, 0, 0, 100]
+        );
+    }
+
+    #[test]
+    fn full_scale() {
+        let buf: Vec<u8> = generate_tone(20_000_000, 1_234_567.0, 1000, -128);
+        assert_eq!(buf.len(), 2000);
+        assert!(buf.chunks_exact(2).all(|iq| {
+            let i: f64 = f64::from(iq[0] as i8);
+            let q: f64 = f64::from(iq[1] as i8);
+            ((i * i + q * q).sqrt() - 128.0).abs() < 2.0
+        }));
+    }
+
+    #[test]
+    fn empty() {
+        assert!(generate_tone(10_000_000, 1.0, 0, 127).is_empty());
+    }
+}
+
+/// Generate a linear frequency modulated (FM) chirp.
+///
+/// Returns `duration_samples` interleaved signed 8-bit IQ samples of a
+/// complex baseband chirp, in the same format used for transmitting.
+/// The instantaneous frequency sweeps linearly from `f_start` to `f_stop`:
+///
+/// ```text
+/// f(t) = f_start + (f_stop - f_start) * t / T
+/// ```
+///
+/// where `t = n / sample_rate_hz` and `T = duration_samples / sample_rate_hz`.
+/// As with [`generate_tone`] the frequencies are relative to the center
+/// frequency, negative frequencies are below the center frequency.
+///
+/// A chirp transmitted in loopback characterizes the frequency response of
+/// a receiver in one capture.
+///
+/// # Example
+///
+/// Sweep from 1 MHz below to 1 MHz above the center frequency in 10 ms.
+///
+/// ```
+/// use hackrfone::generate_chirp;
+///
+/// let buf: Vec<u8> = generate_chirp(10_000_000, -1e6, 1e6, 100_000, 127);
+/// assert_eq!(buf.len(), 200_000);
+/// ```
+pub fn generate_chirp(
+    sample_rate_hz: u32,
+    f_start: f32,
+    f_stop: f32,
+    duration_samples: usize,
+    amplitude: i8,
+) -> Vec<u8> {
+    let fs: f64 = f64::from(sample_rate_hz);
+    let f_start: f64 = f64::from(f_start);
+    let duration: f64 = (duration_samples as f64) / fs;
+    let rate: f64 = (f64::from(f_stop) - f_start) / duration;
+    let amplitude: f64 = f64::from(amplitude);
+
+    (0..duration_samples)
+        .flat_map(|n| {
+            let t: f64 = (n as f64) / fs;
+            let phase: f64 = std::f64::consts::TAU * (f_start * t + 0.5 * rate * t * t);
+            [
+                (amplitude * phase.cos()).round() as i8 as u8,
+                (amplitude * phase.sin()).round() as i8 as u8,
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod generate_chirp {
+    use super::{generate_chirp, generate_tone};
+
+    #[test]
+    fn constant_frequency() {
+        assert_eq!(
+            generate_chirp(8_000_000, 2_000_000.0, 2_000_000.0, 64, 100),
+            generate_tone(8_000_000, 2_000_000.0, 64, 100)
+        );
+    }
+
+    #[test]
+    fn sweep() {
+        // instantaneous frequency from 0 to fs / 4 over 4 samples:
+        // phase(n) = 2π * (fs / 4) / (2 * 4) * n² / fs = π n² / 16
+        let buf: Vec<u8> = generate_chirp(1_000, 0.0, 250.0, 4, 100);
+        let expected: Vec<u8> = (0..4)
+            .flat_map(|n| {
+                let phase: f64 = std::f64::consts::PI * f64::from(n * n) / 16.0;
+                [
+                    (100.0 * phase.cos()).round() as i8 as u8,
+                    (100.0 * phase.sin()).round() as i8 as u8,
+                ]
+            })
+            .collect();
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn empty() {
+        assert!(generate_chirp(10_000_000, 0.0, 1.0, 0, 127).is_empty());
+    }
+}
+
+/// Reassemble IQ samples across transfer boundaries.
+///
+/// A bulk transfer can end on an odd byte, splitting an IQ pair across two
+/// transfers.
+/// Processing each transfer with `chunks_exact(2)` drops that byte and swaps
+/// I and Q for the remainder of the stream.
+/// The reassembler carries the odd byte over to the next transfer so samples
+/// stay aligned.
+///
+/// # Example
+///
+/// ```
+/// use hackrfone::IqReassembler;
+///
+/// let mut reassembler: IqReassembler = IqReassembler::new();
+/// let mut samples: Vec<(i8, i8)> = Vec::new();
+/// for transfer in [&[1, 2, 3][..], &[4, 5, 6]] {
+///     reassembler.push(transfer, |i, q| samples.push((i as i8, q as i8)));
+/// }
+/// assert_eq!(samples, [(1, 2), (3, 4), (5, 6)]);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IqReassembler {
+    leftover: Option<u8>,
+}
+
+impl IqReassembler {
+    /// Create a new reassembler.
+    pub const fn new() -> IqReassembler {
+        IqReassembler { leftover: None }
+    }
+
+    /// Push a transfer, calling `f(i, q)` for every complete sample.
+    ///
+    /// The raw bytes are passed to `f`, use [`iq_to_cplx_i8`] or
+    /// [`iq_to_cplx_f32`] to convert them.
+    ///
+    /// [`iq_to_cplx_i8`]: crate::iq_to_cplx_i8
+    /// [`iq_to_cplx_f32`]: crate::iq_to_cplx_f32
+    #[cfg_attr(not(feature = "num-complex"), allow(rustdoc::broken_intra_doc_links))]
+    pub fn push<F: FnMut(u8, u8)>(&mut self, buf: &[u8], mut f: F) {
+        let buf: &[u8] = match (self.leftover.take(), buf.split_first()) {
+            (Some(i), Some((&q, rest))) => {
+                f(i, q);
+                rest
+            }
+            (leftover, _) => {
+                self.leftover = leftover;
+                buf
+            }
+        };
+
+        let mut chunks = buf.chunks_exact(2);
+        chunks.by_ref().for_each(|iq| f(iq[0], iq[1]));
+        if let [i] = chunks.remainder() {
+            self.leftover = Some(*i);
+        }
+    }
+
+    /// Returns `true` if a byte is waiting for its pair.
+    pub const fn has_leftover(&self) -> bool {
+        self.leftover.is_some()
+    }
+
+    /// Discard any leftover byte, use this when starting a new stream.
+    pub fn reset(&mut self) {
+        self.leftover = None;
+    }
+}
+
+#[cfg(test)]
+mod iq_reassembler {
+    use super::IqReassembler;
+
+    fn push(reassembler: &mut IqReassembler, buf: &[u8]) -> Vec<(u8, u8)> {
+        let mut out: Vec<(u8, u8)> = Vec::new();
+        reassembler.push(buf, |i, q| out.push((i, q)));
+        out
+    }
+
+    #[test]
+    fn aligned() {
+        let mut reassembler: IqReassembler = IqReassembler::new();
+        assert_eq!(push(&mut reassembler, &[1, 2, 3, 4]), [(1, 2), (3, 4)]);
+        assert!(!reassembler.has_leftover());
+    }
+
+    #[test]
+    fn split() {
+        let mut reassembler: IqReassembler = IqReassembler::new();
+        assert_eq!(push(&mut reassembler, &[1, 2, 3]), [(1, 2)]);
+        assert!(reassembler.has_leftover());
+        assert_eq!(push(&mut reassembler, &[4, 5]), [(3, 4)]);
+        assert!(reassembler.has_leftover());
+        assert_eq!(push(&mut reassembler, &[6, 7, 8]), [(5, 6), (7, 8)]);
+        assert!(!reassembler.has_leftover());
+    }
+
+    #[test]
+    fn empty() {
+        let mut reassembler: IqReassembler = IqReassembler::new();
+        assert!(push(&mut reassembler, &[1]).is_empty());
+        assert!(push(&mut reassembler, &[]).is_empty());
+        assert_eq!(push(&mut reassembler, &[2]), [(1, 2)]);
+    }
+
+    #[test]
+    fn reset() {
+        let mut reassembler: IqReassembler = IqReassembler::new();
+        push(&mut reassembler, &[1]);
+        reassembler.reset();
+        assert_eq!(push(&mut reassembler, &[2, 3]), [(2, 3)]);
+    }
+}
+
+#[cfg(test)]
+mod thread_safety {
+    use super::{HackRfOne, HackRfOneDyn, RingCapture, RxMode, RxStream, UnknownMode};
+
+    fn assert_send<T: Send>() {}
+    fn assert_sync<T: Sync>() {}
+
+    #[test]
+    fn hackrfone() {
+        assert_send::<HackRfOne<UnknownMode>>();
+        assert_sync::<HackRfOne<UnknownMode>>();
+        assert_send::<HackRfOne<RxMode>>();
+        assert_sync::<HackRfOne<RxMode>>();
+    }
+
+    #[test]
+    fn hackrfone_dyn() {
+        assert_send::<HackRfOneDyn>();
+        assert_sync::<HackRfOneDyn>();
+    }
+
+    // RxStream is not Sync, checked by a compile_fail doctest on RxStream
+    #[test]
+    fn rx_stream() {
+        assert_send::<RxStream>();
+    }
+
+    #[test]
+    fn ring_capture() {
+        assert_send::<RingCapture>();
+        assert_sync::<RingCapture>();
+    }
 }
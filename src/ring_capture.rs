@@ -0,0 +1,237 @@
+use crate::{aligned_buf::AlignedBuf, Error, HackRfOne, RxMode};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Circular byte buffer holding the most recent samples.
+struct Ring {
+    buf: Vec<u8>,
+    /// Index of the next byte to write.
+    pos: usize,
+    /// Number of valid bytes, saturates at `buf.len()`.
+    len: usize,
+}
+
+impl Ring {
+    fn new(capacity: usize) -> Ring {
+        Ring {
+            buf: vec![0; capacity],
+            pos: 0,
+            len: 0,
+        }
+    }
+
+    fn extend(&mut self, data: &[u8]) {
+        let cap: usize = self.buf.len();
+        // only the tail of an oversized block survives
+        let data: &[u8] = &data[data.len().saturating_sub(cap)..];
+
+        let first: usize = data.len().min(cap - self.pos);
+        self.buf[self.pos..self.pos + first].copy_from_slice(&data[..first]);
+        self.buf[..data.len() - first].copy_from_slice(&data[first..]);
+
+        self.pos = (self.pos + data.len()) % cap;
+        self.len = (self.len + data.len()).min(cap);
+    }
+
+    fn snapshot(&self) -> Vec<u8> {
+        let start: usize = (self.pos + self.buf.len() - self.len) % self.buf.len();
+        let mut out: Vec<u8> = Vec::with_capacity(self.len);
+        if start + self.len <= self.buf.len() {
+            out.extend_from_slice(&self.buf[start..start + self.len]);
+        } else {
+            out.extend_from_slice(&self.buf[start..]);
+            out.extend_from_slice(&self.buf[..self.pos]);
+        }
+        out
+    }
+}
+
+/// Continuous capture into a fixed size ring buffer.
+///
+/// A background thread keeps receiving samples and overwrites the oldest
+/// data once the buffer is full, so the buffer always holds the most recent
+/// window of samples.
+/// This is useful for pre-trigger recording, keep capturing and call
+/// [`snapshot`] when an event occurs.
+///
+/// # Thread safety
+///
+/// `RingCapture` is `Send` and `Sync`; the ring buffer is behind a mutex,
+/// so [`snapshot`] can be called from any thread.
+///
+/// # Example
+///
+/// Keep the last 100 ms of samples at 10 MHz.
+///
+/// ```no_run
+/// use hackrfone::{HackRfOne, RingCapture, RxMode, UnknownMode};
+/// use std::time::Duration;
+///
+/// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+/// radio.set_sample_rate(20_000_000, 2)?;
+/// let radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+///
+/// let capture: RingCapture =
+///     RingCapture::with_duration(radio, Duration::from_millis(100), 10_000_000)?;
+/// // ... wait for a trigger
+/// let window: Vec<u8> = capture.snapshot();
+/// let radio: HackRfOne<RxMode> = capture.stop()?;
+/// radio.stop_rx()?;
+/// # Ok::<(), hackrfone::Error>(())
+/// ```
+///
+/// [`snapshot`]: crate::RingCapture::snapshot
+pub struct RingCapture {
+    ring: Arc<Mutex<Ring>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<Result<HackRfOne<RxMode>, Error>>>,
+}
+
+impl RingCapture {
+    /// Start capturing into a ring buffer of `capacity` bytes.
+    ///
+    /// The capacity is rounded down to a whole number of IQ samples.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if the capacity is less than one sample.
+    pub fn new(radio: HackRfOne<RxMode>, capacity: usize) -> Result<RingCapture, Error> {
+        let capacity: usize = capacity & !1;
+        if capacity == 0 {
+            return Err(Error::Argument);
+        }
+
+        let ring: Arc<Mutex<Ring>> = Arc::new(Mutex::new(Ring::new(capacity)));
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+
+        let thread_ring: Arc<Mutex<Ring>> = Arc::clone(&ring);
+        let thread_stop: Arc<AtomicBool> = Arc::clone(&stop);
+        let thread = thread::spawn(move || -> Result<HackRfOne<RxMode>, Error> {
+            let mut radio: HackRfOne<RxMode> = radio;
+            let mut buf: AlignedBuf = AlignedBuf::new(radio.rx_mtu());
+            while !thread_stop.load(Ordering::Relaxed) {
+                let n: usize = radio.rx_into(&mut buf)?;
+                thread_ring
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .extend(&buf[..n]);
+            }
+            Ok(radio)
+        });
+
+        Ok(RingCapture {
+            ring,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Start capturing into a ring buffer that holds `duration` worth of
+    /// samples at `sample_rate_hz`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if the window is less than one sample.
+    pub fn with_duration(
+        radio: HackRfOne<RxMode>,
+        duration: Duration,
+        sample_rate_hz: u32,
+    ) -> Result<RingCapture, Error> {
+        let samples: f64 = duration.as_secs_f64() * f64::from(sample_rate_hz);
+        RingCapture::new(radio, (samples as usize).saturating_mul(2))
+    }
+
+    /// Capacity of the ring buffer in bytes.
+    pub fn capacity(&self) -> usize {
+        self.ring
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .buf
+            .len()
+    }
+
+    /// Copy out the current window of samples, oldest first.
+    ///
+    /// The returned buffer is shorter than the capacity until the ring has
+    /// filled up.
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.ring
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .snapshot()
+    }
+
+    /// Stop capturing and return the radio.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error that stopped the capture thread, if any.
+    /// The radio is dropped in this case.
+    pub fn stop(mut self) -> Result<HackRfOne<RxMode>, Error> {
+        self.join()
+    }
+
+    fn join(&mut self) -> Result<HackRfOne<RxMode>, Error> {
+        self.stop.store(true, Ordering::Relaxed);
+        match self.thread.take() {
+            Some(thread) => match thread.join() {
+                Ok(result) => result,
+                Err(e) => std::panic::resume_unwind(e),
+            },
+            None => Err(Error::Usb(rusb::Error::NoDevice)),
+        }
+    }
+}
+
+impl Drop for RingCapture {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod ring {
+    use super::Ring;
+
+    #[test]
+    fn partial() {
+        let mut ring: Ring = Ring::new(8);
+        assert!(ring.snapshot().is_empty());
+        ring.extend(&[1, 2, 3]);
+        assert_eq!(ring.snapshot(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn wrap() {
+        let mut ring: Ring = Ring::new(4);
+        ring.extend(&[1, 2, 3]);
+        ring.extend(&[4, 5, 6]);
+        assert_eq!(ring.snapshot(), [3, 4, 5, 6]);
+        ring.extend(&[7]);
+        assert_eq!(ring.snapshot(), [4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn exact() {
+        let mut ring: Ring = Ring::new(4);
+        ring.extend(&[1, 2, 3, 4]);
+        assert_eq!(ring.snapshot(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn oversized() {
+        let mut ring: Ring = Ring::new(4);
+        ring.extend(&[1]);
+        ring.extend(&[2, 3, 4, 5, 6, 7]);
+        assert_eq!(ring.snapshot(), [4, 5, 6, 7]);
+    }
+}
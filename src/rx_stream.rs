@@ -0,0 +1,483 @@
+use crate::{Error, HackRfOne, RxMode};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Queued RX stream.
+///
+/// A worker thread performs bulk transfers back to back and queues the
+/// received buffers, up to the [transfer count] deep, so the device keeps
+/// streaming while the consumer processes earlier transfers.
+/// `rusb` does not expose the asynchronous libusb API, the worker thread
+/// takes the place of the in-flight transfers used by `libhackrf`.
+///
+/// Create a stream with [`into_stream`].
+///
+/// # Threading model
+///
+/// Each stream has exactly one worker thread, which owns the radio and
+/// makes blocking bulk transfers; there is no separate libusb event loop.
+/// The thread is started by [`into_stream`] and joined by [`stop`], or
+/// when the stream is dropped, so it never outlives the stream.
+/// Stopping waits for the transfer in progress, at most the USB timeout.
+///
+/// A stream can be paused with [`pause`], the worker then sleeps without
+/// using the CPU until [`resume`] or [`stop`] is called.
+/// Applications with several radios can pause the streams that are not
+/// needed instead of stopping them.
+///
+/// # Thread safety
+///
+/// `RxStream` is `Send`; the radio is owned by the worker thread and the
+/// stream itself only holds channels to it, so the stream can be moved to
+/// the thread that consumes the samples.
+/// It is not `Sync`, samples must be received from one thread at a time.
+///
+/// ```compile_fail
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<hackrfone::RxStream>();
+/// ```
+///
+/// # Example
+///
+/// ```no_run
+/// use hackrfone::{HackRfOne, RxMode, RxStream, UnknownMode};
+///
+/// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+/// radio.set_transfer_count(8)?;
+/// let radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+/// let stream: RxStream = radio.into_stream();
+/// for _ in 0..100 {
+///     let buf: Vec<u8> = stream.recv()?;
+///     // .. do whatever you want with buf here
+///     stream.recycle(buf);
+/// }
+/// let radio: HackRfOne<RxMode> = stream.stop();
+/// radio.stop_rx()?;
+/// # Ok::<(), hackrfone::Error>(())
+/// ```
+///
+/// [transfer count]: crate::HackRfOne::set_transfer_count
+/// [`into_stream`]: crate::HackRfOne::into_stream
+/// [`pause`]: crate::RxStream::pause
+/// [`resume`]: crate::RxStream::resume
+/// [`stop`]: crate::RxStream::stop
+pub struct RxStream {
+    pipeline: Pipeline<HackRfOne<RxMode>>,
+}
+
+/// Source of the transfers received by the worker thread.
+trait Source: Send + 'static {
+    /// Size of a single transfer in bytes.
+    fn transfer_size(&self) -> usize;
+
+    /// Receive one transfer into `buf`, returns the number of bytes.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error>;
+}
+
+impl Source for HackRfOne<RxMode> {
+    fn transfer_size(&self) -> usize {
+        self.transfer_size
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        Ok(self.read_bulk(buf)?)
+    }
+}
+
+/// Worker thread and the channels to it, generic over the source so the
+/// lifecycle can be tested without a device.
+struct Pipeline<S> {
+    data: Option<Receiver<Result<Vec<u8>, Error>>>,
+    recycle: Sender<Vec<u8>>,
+    stop: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    queue: Arc<QueueDepth>,
+    thread: Option<JoinHandle<S>>,
+}
+
+/// Received transfers waiting in the queue.
+#[derive(Default)]
+struct QueueDepth {
+    transfers: AtomicUsize,
+    bytes: AtomicUsize,
+}
+
+impl QueueDepth {
+    fn push(&self, len: usize) {
+        self.bytes.fetch_add(len, Ordering::Relaxed);
+        self.transfers.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn pop(&self, len: usize) {
+        self.transfers.fetch_sub(1, Ordering::Relaxed);
+        self.bytes.fetch_sub(len, Ordering::Relaxed);
+    }
+}
+
+impl RxStream {
+    pub(crate) fn new(radio: HackRfOne<RxMode>) -> RxStream {
+        let transfer_count: usize = radio.transfer_count;
+        RxStream {
+            pipeline: Pipeline::new(radio, transfer_count),
+        }
+    }
+
+    /// Receive the next transfer, blocking until one is available.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error that stopped the worker thread.
+    /// Subsequent calls return `Error::Usb(rusb::Error::NoDevice)`.
+    pub fn recv(&self) -> Result<Vec<u8>, Error> {
+        self.pipeline.recv()
+    }
+
+    /// Receive the next transfer, waiting at most `timeout`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Usb(rusb::Error::Timeout)` if no transfer arrived
+    /// within the timeout, otherwise the same errors as [`recv`].
+    ///
+    /// [`recv`]: crate::RxStream::recv
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Vec<u8>, Error> {
+        self.pipeline.recv_timeout(timeout)
+    }
+
+    /// Number of received transfers waiting to be taken with [`recv`].
+    ///
+    /// The queue holds at most the [transfer count] of transfers.
+    /// A full queue means samples are consumed slower than they arrive,
+    /// once full no more transfers are made and the device drops samples
+    /// until a transfer is taken.
+    ///
+    /// The count includes a transfer completed while the queue is full,
+    /// it is counted while the worker thread waits to queue it, so the count
+    /// is at most the transfer count plus one.
+    ///
+    /// [`recv`]: crate::RxStream::recv
+    /// [transfer count]: crate::HackRfOne::set_transfer_count
+    pub fn pending_transfers(&self) -> usize {
+        self.pipeline.queue.transfers.load(Ordering::Relaxed)
+    }
+
+    /// Number of sample bytes in the transfers waiting to be taken with
+    /// [`recv`].
+    ///
+    /// Divide by twice the sample rate for the latency of the queue in
+    /// seconds.
+    ///
+    /// [`recv`]: crate::RxStream::recv
+    pub fn queued_bytes(&self) -> usize {
+        self.pipeline.queue.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Return a buffer to the stream for reuse.
+    ///
+    /// This is optional, buffers that are not returned are replaced with new
+    /// allocations.
+    pub fn recycle(&self, buf: Vec<u8>) {
+        self.pipeline.recycle.send(buf).ok();
+    }
+
+    /// Pause the stream.
+    ///
+    /// The worker thread finishes the transfer in progress, then sleeps
+    /// until [`resume`] is called.
+    /// Transfers already queued can still be received.
+    ///
+    /// The radio keeps streaming while paused, samples are dropped by the
+    /// device until the stream is resumed.
+    ///
+    /// [`resume`]: crate::RxStream::resume
+    pub fn pause(&self) {
+        self.pipeline.pause();
+    }
+
+    /// Resume a stream paused with [`pause`].
+    ///
+    /// [`pause`]: crate::RxStream::pause
+    pub fn resume(&self) {
+        self.pipeline.resume();
+    }
+
+    /// Returns `true` if the stream is paused.
+    pub fn is_paused(&self) -> bool {
+        self.pipeline.paused.load(Ordering::Acquire)
+    }
+
+    /// Stop the stream and return the radio.
+    ///
+    /// Queued transfers that have not been received are discarded.
+    pub fn stop(mut self) -> HackRfOne<RxMode> {
+        self.pipeline
+            .join()
+            .expect("RX stream worker already joined")
+    }
+}
+
+impl<S: Source> Pipeline<S> {
+    fn new(source: S, transfer_count: usize) -> Pipeline<S> {
+        let (data_tx, data_rx) = mpsc::sync_channel(transfer_count);
+        let (recycle_tx, recycle_rx) = mpsc::channel();
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let paused: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let queue: Arc<QueueDepth> = Arc::default();
+
+        let thread_stop: Arc<AtomicBool> = Arc::clone(&stop);
+        let thread_paused: Arc<AtomicBool> = Arc::clone(&paused);
+        let thread_queue: Arc<QueueDepth> = Arc::clone(&queue);
+        let thread = thread::spawn(move || {
+            worker(
+                source,
+                data_tx,
+                recycle_rx,
+                thread_stop,
+                thread_paused,
+                thread_queue,
+            )
+        });
+
+        Pipeline {
+            data: Some(data_rx),
+            recycle: recycle_tx,
+            stop,
+            paused,
+            queue,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl<S> Pipeline<S> {
+    fn data(&self) -> &Receiver<Result<Vec<u8>, Error>> {
+        // only taken when stopping
+        self.data.as_ref().unwrap()
+    }
+
+    fn recv(&self) -> Result<Vec<u8>, Error> {
+        self.received(
+            self.data()
+                .recv()
+                .unwrap_or(Err(Error::Usb(rusb::Error::NoDevice))),
+        )
+    }
+
+    fn recv_timeout(&self, timeout: Duration) -> Result<Vec<u8>, Error> {
+        match self.data().recv_timeout(timeout) {
+            Ok(result) => self.received(result),
+            Err(RecvTimeoutError::Timeout) => Err(Error::Usb(rusb::Error::Timeout)),
+            Err(RecvTimeoutError::Disconnected) => Err(Error::Usb(rusb::Error::NoDevice)),
+        }
+    }
+
+    fn received(&self, result: Result<Vec<u8>, Error>) -> Result<Vec<u8>, Error> {
+        if let Ok(buf) = &result {
+            self.queue.pop(buf.len());
+        }
+        result
+    }
+
+    fn pause(&self) {
+        self.paused.store(true, Ordering::Release);
+    }
+
+    fn resume(&self) {
+        self.paused.store(false, Ordering::Release);
+        self.unpark();
+    }
+
+    fn unpark(&self) {
+        if let Some(thread) = &self.thread {
+            thread.thread().unpark();
+        }
+    }
+
+    fn join(&mut self) -> Option<S> {
+        self.stop.store(true, Ordering::Relaxed);
+        // unblock a worker waiting on a full queue, or paused
+        drop(self.data.take());
+        self.unpark();
+        self.thread.take().map(|thread| match thread.join() {
+            Ok(source) => source,
+            Err(e) => std::panic::resume_unwind(e),
+        })
+    }
+}
+
+impl<S> Drop for Pipeline<S> {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        drop(self.data.take());
+        self.unpark();
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+fn worker<S: Source>(
+    mut source: S,
+    data: SyncSender<Result<Vec<u8>, Error>>,
+    recycle: Receiver<Vec<u8>>,
+    stop: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    queue: Arc<QueueDepth>,
+) -> S {
+    while !stop.load(Ordering::Relaxed) {
+        if paused.load(Ordering::Acquire) {
+            // woken by resume or stop, spurious wake ups loop back here
+            thread::park();
+            continue;
+        }
+
+        let transfer_size: usize = source.transfer_size();
+        let mut buf: Vec<u8> = recycle.try_recv().unwrap_or_default();
+        buf.resize(transfer_size, 0);
+
+        let result: Result<Vec<u8>, Error> = source.read(&mut buf).map(|n| {
+            buf.truncate(n);
+            buf
+        });
+        let failed: bool = result.is_err();
+        // counted before sending, recv can take the transfer before send
+        // returns and the count must not go below zero
+        if let Ok(buf) = &result {
+            queue.push(buf.len());
+        }
+
+        if data.send(result).is_err() || failed {
+            break;
+        }
+    }
+    source
+}
+
+#[cfg(test)]
+mod pipeline {
+    use super::{Ordering, Pipeline, Source};
+    use crate::Error;
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_millis(100);
+
+    /// Source counting its transfers, fails after `limit` transfers.
+    struct Counter {
+        reads: usize,
+        limit: usize,
+    }
+
+    impl Counter {
+        fn new() -> Counter {
+            Counter {
+                reads: 0,
+                limit: usize::MAX,
+            }
+        }
+    }
+
+    impl Source for Counter {
+        fn transfer_size(&self) -> usize {
+            4
+        }
+
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+            if self.reads == self.limit {
+                return Err(Error::Usb(rusb::Error::Pipe));
+            }
+            self.reads += 1;
+            std::thread::sleep(Duration::from_millis(1));
+            buf.fill(self.reads as u8);
+            Ok(buf.len())
+        }
+    }
+
+    /// Receive until no transfer arrives within the timeout.
+    fn drain(pipeline: &Pipeline<Counter>) {
+        while pipeline.recv_timeout(TIMEOUT).is_ok() {}
+    }
+
+    #[test]
+    fn recv() {
+        let pipeline: Pipeline<Counter> = Pipeline::new(Counter::new(), 4);
+        let buf: Vec<u8> = pipeline.recv().unwrap();
+        assert_eq!(&buf[..], [1; 4]);
+    }
+
+    #[test]
+    fn pause() {
+        let pipeline: Pipeline<Counter> = Pipeline::new(Counter::new(), 4);
+        pipeline.recv().unwrap();
+        pipeline.pause();
+        drain(&pipeline);
+        assert_eq!(
+            pipeline.recv_timeout(TIMEOUT).unwrap_err(),
+            Error::Usb(rusb::Error::Timeout)
+        );
+        assert_eq!(pipeline.queue.transfers.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn resume() {
+        let pipeline: Pipeline<Counter> = Pipeline::new(Counter::new(), 4);
+        pipeline.pause();
+        drain(&pipeline);
+        pipeline.resume();
+        assert!(pipeline.recv_timeout(TIMEOUT).is_ok());
+    }
+
+    #[test]
+    fn stop_while_paused() {
+        let mut pipeline: Pipeline<Counter> = Pipeline::new(Counter::new(), 4);
+        pipeline.recv().unwrap();
+        pipeline.pause();
+        drain(&pipeline);
+        let counter: Counter = pipeline.join().unwrap();
+        assert!(counter.reads >= 1);
+    }
+
+    #[test]
+    fn stop_while_paused_full_queue() {
+        let mut pipeline: Pipeline<Counter> = Pipeline::new(Counter::new(), 1);
+        // let the worker block on the full queue before pausing
+        std::thread::sleep(TIMEOUT);
+        pipeline.pause();
+        assert!(pipeline.join().is_some());
+    }
+
+    #[test]
+    fn full_queue_depth() {
+        let pipeline: Pipeline<Counter> = Pipeline::new(Counter::new(), 2);
+        // let the worker block on the full queue
+        std::thread::sleep(TIMEOUT);
+        assert_eq!(pipeline.queue.transfers.load(Ordering::Relaxed), 3);
+        assert_eq!(pipeline.queue.bytes.load(Ordering::Relaxed), 3 * 4);
+    }
+
+    #[test]
+    fn drop_while_paused() {
+        let pipeline: Pipeline<Counter> = Pipeline::new(Counter::new(), 4);
+        pipeline.pause();
+        drain(&pipeline);
+        drop(pipeline);
+    }
+
+    #[test]
+    fn error_stops_worker() {
+        let counter: Counter = Counter { reads: 0, limit: 1 };
+        let pipeline: Pipeline<Counter> = Pipeline::new(counter, 4);
+        assert!(pipeline.recv().is_ok());
+        assert_eq!(pipeline.recv().unwrap_err(), Error::Usb(rusb::Error::Pipe));
+        assert_eq!(
+            pipeline.recv().unwrap_err(),
+            Error::Usb(rusb::Error::NoDevice)
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{samples_to_cplx_f32_into, Error, HackRfOne, RxMode};
+use num_complex::Complex;
+use std::time::Duration;
+
+/// Receive stream with SoapySDR style reads.
+///
+/// This adapts [`HackRfOne::rx_into`] to the `readStream` call of the
+/// SoapySDR streaming API, to ease porting code written against SoapySDR.
+///
+/// Only a subset of the SoapySDR semantics is supported:
+///
+/// * A single channel, `buffs` must have exactly one buffer.
+/// * The only sample format is `CF32`, complex `f32` scaled like
+///   [`iq_to_cplx_f32`], not normalized to ±1.
+/// * There are no flags or timestamps, and no `activateStream`; the stream
+///   is active from creation until [`into_inner`].
+/// * Errors are returned as [`Error`] instead of negative return codes;
+///   a timeout is `Error::Usb(rusb::Error::Timeout)`.
+///
+/// Like SoapySDR a read returns at most the number of samples that fit in
+/// the buffer, samples left over from a USB transfer are returned by the
+/// next read.
+///
+/// # Example
+///
+/// ```no_run
+/// use hackrfone::{num_complex::Complex, HackRfOne, RxMode, SoapyStream, UnknownMode};
+///
+/// let mut radio: HackRfOne<UnknownMode> = HackRfOne::new().unwrap();
+/// let radio: HackRfOne<RxMode> = radio.into_rx_mode()?;
+/// let mut stream: SoapyStream = SoapyStream::new(radio);
+///
+/// let mut buf: Vec<Complex<f32>> = vec![Complex::new(0.0, 0.0); stream.mtu()];
+/// let n: usize = stream.read_stream(&mut [&mut buf], 100_000)?;
+/// // .. do whatever you want with buf[..n] here
+///
+/// stream.into_inner().stop_rx()?;
+/// # Ok::<(), hackrfone::Error>(())
+/// ```
+///
+/// [`iq_to_cplx_f32`]: crate::iq_to_cplx_f32
+/// [`into_inner`]: crate::SoapyStream::into_inner
+pub struct SoapyStream {
+    radio: HackRfOne<RxMode>,
+    buf: Vec<u8>,
+    /// Offset of the next sample in `buf`.
+    pos: usize,
+}
+
+impl SoapyStream {
+    /// Create a new stream from a receiving radio.
+    pub fn new(radio: HackRfOne<RxMode>) -> SoapyStream {
+        SoapyStream {
+            radio,
+            buf: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Number of samples in one USB transfer, the equivalent of
+    /// `getStreamMTU`.
+    ///
+    /// Reads into buffers of this size avoid holding samples over between
+    /// reads.
+    pub fn mtu(&self) -> usize {
+        self.radio.rx_mtu() / 2
+    }
+
+    /// Read samples into the single buffer in `buffs`.
+    ///
+    /// A USB transfer is made only when no samples are left over from the
+    /// previous one, with a timeout of `timeout_us` microseconds.
+    /// The timeout is rounded down to whole milliseconds, with a minimum of
+    /// 1 ms, a non-blocking read is not possible.
+    ///
+    /// Returns the number of samples written to the buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Argument`] if `buffs` does not have exactly one
+    /// buffer.
+    pub fn read_stream(
+        &mut self,
+        buffs: &mut [&mut [Complex<f32>]],
+        timeout_us: i64,
+    ) -> Result<usize, Error> {
+        let [out] = buffs else {
+            return Err(Error::Argument);
+        };
+        if out.is_empty() {
+            return Ok(0);
+        }
+
+        if self.buf.len().saturating_sub(self.pos) < 2 {
+            let timeout: Duration = Duration::from_micros(timeout_us.max(1000) as u64);
+            let to: Duration = std::mem::replace(&mut self.radio.to, timeout);
+            self.buf.resize(self.radio.rx_mtu(), 0);
+            let result: Result<usize, Error> = self.radio.rx_into(&mut self.buf);
+            self.radio.to = to;
+            let n: usize = result.inspect_err(|_| self.buf.clear())?;
+            self.buf.truncate(n);
+            self.pos = 0;
+        }
+
+        let n: usize = samples_to_cplx_f32_into(&self.buf[self.pos..], out);
+        self.pos += n * 2;
+        Ok(n)
+    }
+
+    /// Stop the stream and return the radio.
+    ///
+    /// Samples left over from the last USB transfer are discarded.
+    pub fn into_inner(self) -> HackRfOne<RxMode> {
+        self.radio
+    }
+}
@@ -0,0 +1,73 @@
+//! Protocol trace of USB transfers.
+//!
+//! With the `log` feature enabled every control and bulk transfer made by
+//! this crate is emitted as a debug level [`log`] record with the
+//! `hackrfone::trace` target, for any logger installed by the application.
+//! Without the feature the trace points compile to nothing.
+//!
+//! [`log`]: https://docs.rs/log
+
+#[cfg(feature = "log")]
+fn log_control(
+    dir: rusb::Direction,
+    request: &str,
+    request_id: u8,
+    value: u16,
+    index: u16,
+    len: usize,
+    result: &Result<usize, rusb::Error>,
+) {
+    log::debug!(
+        "control {:?} {} ({:#04X}) value={:#06X} index={:#06X} len={} result={:?}",
+        dir,
+        request,
+        request_id,
+        value,
+        index,
+        len,
+        result
+    );
+}
+
+#[inline(always)]
+#[allow(unused_variables)]
+pub(crate) fn control(
+    dir: rusb::Direction,
+    request: crate::Request,
+    value: u16,
+    index: u16,
+    len: usize,
+    result: &Result<usize, rusb::Error>,
+) {
+    #[cfg(feature = "log")]
+    log_control(
+        dir,
+        request.name(),
+        request.into(),
+        value,
+        index,
+        len,
+        result,
+    );
+}
+
+#[inline(always)]
+#[allow(unused_variables)]
+pub(crate) fn raw_control(
+    dir: rusb::Direction,
+    request: u8,
+    value: u16,
+    index: u16,
+    len: usize,
+    result: &Result<usize, rusb::Error>,
+) {
+    #[cfg(feature = "log")]
+    log_control(dir, "Raw", request, value, index, len, result);
+}
+
+#[inline(always)]
+#[allow(unused_variables)]
+pub(crate) fn bulk(endpoint: u8, len: usize, result: &Result<usize, rusb::Error>) {
+    #[cfg(feature = "log")]
+    log::debug!("bulk {:#04X} len={} result={:?}", endpoint, len, result);
+}
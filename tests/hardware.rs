@@ -0,0 +1,97 @@
+//! Tests against a real HackRF One.
+//!
+//! These require a connected device, they are only built with the
+//! `hardware-tests` feature and are ignored by default so that
+//! `cargo test --all-features` passes without a device:
+//!
+//! ```text
+//! cargo test --features hardware-tests -- --ignored --test-threads=1
+//! ```
+#![cfg(feature = "hardware-tests")]
+
+use hackrfone::{HackRfOne, RxMode, UnknownMode};
+use std::time::Duration;
+
+fn open() -> HackRfOne<UnknownMode> {
+    HackRfOne::new().expect("Failed to open HackRF One")
+}
+
+#[test]
+#[ignore = "requires a HackRF One"]
+fn board_id() {
+    let radio: HackRfOne<UnknownMode> = open();
+    let id: u8 = radio.board_id().expect("Failed to read board ID");
+    // 0xFE and 0xFF are "undetected" and "unrecognized"
+    assert!(id < 0xFE, "unexpected board ID {:#04X}", id);
+}
+
+#[test]
+#[ignore = "requires a HackRF One"]
+fn version() {
+    let radio: HackRfOne<UnknownMode> = open();
+    let version: String = radio.version().expect("Failed to read version");
+    assert!(!version.is_empty());
+    assert!(version.is_ascii(), "version is not ASCII: {:?}", version);
+}
+
+#[test]
+#[ignore = "requires a HackRF One"]
+fn rx_statistics() {
+    let mut radio: HackRfOne<UnknownMode> = open();
+    radio
+        .set_sample_rate(20_000_000, 2)
+        .expect("Failed to set sample rate");
+    radio
+        .set_freq(915_000_000)
+        .expect("Failed to set frequency");
+    radio
+        .set_amp_enable(false)
+        .expect("Failed to disable amplifier");
+    radio.set_lna_gain(16).expect("Failed to set LNA gain");
+    radio.set_vga_gain(16).expect("Failed to set VGA gain");
+    let mut radio: HackRfOne<RxMode> = radio.into_rx_mode().expect("Failed to enter RX mode");
+
+    // the first transfer may contain samples from before the radio settled
+    radio.rx().expect("Failed to receive");
+    let data: Vec<u8> = radio.rx().expect("Failed to receive");
+    radio.stop_rx().expect("Failed to stop RX");
+
+    assert!(!data.is_empty());
+    assert_eq!(data.len() % 2, 0);
+    assert!(data.iter().any(|&b| b != data[0]), "samples are constant");
+
+    let mean_i: f64 = data
+        .iter()
+        .step_by(2)
+        .map(|&b| f64::from(b as i8))
+        .sum::<f64>()
+        / (data.len() / 2) as f64;
+    let mean_q: f64 = data
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .map(|&b| f64::from(b as i8))
+        .sum::<f64>()
+        / (data.len() / 2) as f64;
+
+    // the HackRF has a DC spike, but the mean should be well within range
+    assert!(mean_i.abs() < 16.0, "I mean is {}", mean_i);
+    assert!(mean_q.abs() < 16.0, "Q mean is {}", mean_q);
+}
+
+#[test]
+#[ignore = "requires a HackRF One"]
+fn reset_while_streaming() {
+    let radio: HackRfOne<UnknownMode> = open();
+    let mut radio: HackRfOne<RxMode> = radio.into_rx_mode().expect("Failed to enter RX mode");
+    radio.rx().expect("Failed to receive");
+
+    // the transceiver is turned off before the reset, a reset while
+    // streaming hangs some firmware versions
+    let radio: HackRfOne<UnknownMode> = radio
+        .reset_and_reopen(Duration::from_secs(5))
+        .expect("Failed to reset and reopen");
+    radio
+        .board_id()
+        .expect("Failed to read board ID after reset");
+}